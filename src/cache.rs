@@ -0,0 +1,384 @@
+//! TTL cache for the reference-data endpoints (currencies, autocomplete,
+//! airport lookup).
+//!
+//! Entries live in memory and, when a directory is configured, as one JSON
+//! file per key under `<dir>/<endpoint>/`. Disk failures are logged and
+//! treated as misses; the cache never fails a request.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Currencies,
+    Autocomplete,
+    Airport,
+}
+
+impl Endpoint {
+    pub const ALL: [Self; 3] = [Self::Currencies, Self::Autocomplete, Self::Airport];
+
+    pub fn default_ttl(self) -> Duration {
+        const DAY: u64 = 24 * 60 * 60;
+        match self {
+            Self::Currencies => Duration::from_secs(7 * DAY),
+            Self::Autocomplete => Duration::from_secs(DAY),
+            Self::Airport => Duration::from_secs(30 * DAY),
+        }
+    }
+
+    /// Normalize the query so equivalent lookups share an entry.
+    fn normalize(self, query: &str) -> String {
+        match self {
+            Self::Currencies => String::new(),
+            Self::Autocomplete => query.trim().to_lowercase(),
+            Self::Airport => query.trim().to_uppercase(),
+        }
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Currencies => f.write_str("currencies"),
+            Self::Autocomplete => f.write_str("autocomplete"),
+            Self::Airport => f.write_str("airport"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve fresh entries, fetch and store on a miss.
+    #[default]
+    Normal,
+    /// Ignore existing entries, always fetch and overwrite.
+    Refresh,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CacheConfig {
+    dir: Option<PathBuf>,
+    mode: CacheMode,
+    ttls: HashMap<Endpoint, Duration>,
+}
+
+impl CacheConfig {
+    /// Memory only, default TTLs.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    #[must_use]
+    pub fn mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    fn ttl_for(&self, endpoint: Endpoint) -> Duration {
+        self.ttls
+            .get(&endpoint)
+            .copied()
+            .unwrap_or_else(|| endpoint.default_ttl())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    query: String,
+    /// Seconds since the Unix epoch.
+    stored_at: u64,
+    value: Value,
+}
+
+pub struct Cache {
+    config: CacheConfig,
+    memory: Mutex<HashMap<(Endpoint, String), Entry>>,
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            memory: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.config.mode
+    }
+
+    /// A fresh entry, from memory or disk. Always `None` in refresh mode.
+    pub fn get<T: DeserializeOwned>(&self, endpoint: Endpoint, query: &str) -> Option<T> {
+        if self.config.mode == CacheMode::Refresh {
+            return None;
+        }
+        let key = (endpoint, endpoint.normalize(query));
+        let ttl = self.config.ttl_for(endpoint);
+
+        // Not held across the disk read, so a slow filesystem doesn't
+        // serialize every lookup.
+        let cached = self
+            .memory
+            .lock()
+            .expect("cache lock poisoned")
+            .get(&key)
+            .cloned();
+        let from_disk = cached.is_none();
+        let entry = match cached {
+            Some(entry) => entry,
+            None => self.read_disk(endpoint, &key.1)?,
+        };
+        if !is_fresh(&entry, ttl) {
+            debug!(%endpoint, query = %key.1, "cache entry expired");
+            self.invalidate(endpoint, query);
+            return None;
+        }
+        if from_disk {
+            self.memory
+                .lock()
+                .expect("cache lock poisoned")
+                .insert(key.clone(), entry.clone());
+        }
+
+        match serde_json::from_value(entry.value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!(%endpoint, query = %key.1, error = %e, "discarding unreadable cache entry");
+                self.invalidate(endpoint, query);
+                None
+            }
+        }
+    }
+
+    pub fn put<T: Serialize>(&self, endpoint: Endpoint, query: &str, value: &T) {
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!(%endpoint, error = %e, "not caching unserializable value");
+                return;
+            }
+        };
+        let query = endpoint.normalize(query);
+        let entry = Entry {
+            query: query.clone(),
+            stored_at: now(),
+            value,
+        };
+        if let Some(path) = self.path(endpoint, &query)
+            && let Err(e) = write_entry(&path, &entry)
+        {
+            warn!(path = %path.display(), error = %e, "failed to write cache entry");
+        }
+        self.memory
+            .lock()
+            .expect("cache lock poisoned")
+            .insert((endpoint, query), entry);
+    }
+
+    pub fn invalidate(&self, endpoint: Endpoint, query: &str) {
+        let query = endpoint.normalize(query);
+        if let Some(path) = self.path(endpoint, &query) {
+            remove_quietly(&path);
+        }
+        self.memory
+            .lock()
+            .expect("cache lock poisoned")
+            .remove(&(endpoint, query));
+    }
+
+    /// Drop every entry for the endpoint.
+    pub fn invalidate_endpoint(&self, endpoint: Endpoint) -> io::Result<()> {
+        self.memory
+            .lock()
+            .expect("cache lock poisoned")
+            .retain(|(e, _), _| *e != endpoint);
+        match &self.config.dir {
+            Some(dir) => remove_dir_if_exists(&dir.join(endpoint.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        for endpoint in Endpoint::ALL {
+            self.invalidate_endpoint(endpoint)?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, endpoint: Endpoint, query: &str) -> Option<PathBuf> {
+        let dir = self.config.dir.as_ref()?;
+        Some(
+            dir.join(endpoint.to_string())
+                .join(format!("{:016x}.json", fnv1a(query.as_bytes()))),
+        )
+    }
+
+    fn read_disk(&self, endpoint: Endpoint, query: &str) -> Option<Entry> {
+        let path = self.path(endpoint, query)?;
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to read cache entry");
+                return None;
+            }
+        };
+        match serde_json::from_slice::<Entry>(&data) {
+            // A different query that hashed to the same file.
+            Ok(entry) if entry.query != query => None,
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "discarding corrupt cache entry");
+                remove_quietly(&path);
+                None
+            }
+        }
+    }
+}
+
+fn is_fresh(entry: &Entry, ttl: Duration) -> bool {
+    now().saturating_sub(entry.stored_at) < ttl.as_secs()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// 64-bit FNV-1a, stable across builds, for file names.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn write_entry(path: &Path, entry: &Entry) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Write then rename so readers never see a partial file.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(entry)?)?;
+    fs::rename(&tmp, path)
+}
+
+fn remove_quietly(path: &Path) {
+    if let Err(e) = fs::remove_file(path)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        warn!(path = %path.display(), error = %e, "failed to remove cache entry");
+    }
+}
+
+fn remove_dir_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_hit_and_normalized_key() {
+        let cache = Cache::new(CacheConfig::new());
+        cache.put(Endpoint::Airport, "jfk ", &"New York");
+        assert_eq!(
+            cache.get::<String>(Endpoint::Airport, "JFK").as_deref(),
+            Some("New York")
+        );
+        assert_eq!(cache.get::<String>(Endpoint::Autocomplete, "JFK"), None);
+    }
+
+    #[test]
+    fn disk_survives_new_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        Cache::new(CacheConfig::new().dir(dir.path())).put(Endpoint::Currencies, "", &vec![1, 2]);
+
+        let cache = Cache::new(CacheConfig::new().dir(dir.path()));
+        assert_eq!(
+            cache.get::<Vec<u32>>(Endpoint::Currencies, ""),
+            Some(vec![1, 2])
+        );
+
+        cache.invalidate(Endpoint::Currencies, "");
+        let cache = Cache::new(CacheConfig::new().dir(dir.path()));
+        assert_eq!(cache.get::<Vec<u32>>(Endpoint::Currencies, ""), None);
+    }
+
+    #[test]
+    fn expired_entries_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig::new()
+            .dir(dir.path())
+            .ttl(Endpoint::Autocomplete, Duration::ZERO);
+        let cache = Cache::new(config);
+        cache.put(Endpoint::Autocomplete, "bos", &1);
+        let path = cache.path(Endpoint::Autocomplete, "bos").unwrap();
+        assert!(path.exists());
+        assert_eq!(cache.get::<u32>(Endpoint::Autocomplete, "bos"), None);
+        assert!(!path.exists());
+
+        // A stale file found by a fresh process is removed too.
+        cache.put(Endpoint::Autocomplete, "bos", &1);
+        let config = CacheConfig::new()
+            .dir(dir.path())
+            .ttl(Endpoint::Autocomplete, Duration::ZERO);
+        assert_eq!(
+            Cache::new(config).get::<u32>(Endpoint::Autocomplete, "bos"),
+            None
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn refresh_mode_skips_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        Cache::new(CacheConfig::new().dir(dir.path())).put(Endpoint::Airport, "LHR", &1);
+
+        let refresh = Cache::new(CacheConfig::new().dir(dir.path()).mode(CacheMode::Refresh));
+        assert_eq!(refresh.get::<u32>(Endpoint::Airport, "LHR"), None);
+        refresh.put(Endpoint::Airport, "LHR", &2);
+
+        let cache = Cache::new(CacheConfig::new().dir(dir.path()));
+        assert_eq!(cache.get::<u32>(Endpoint::Airport, "LHR"), Some(2));
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(CacheConfig::new().dir(dir.path()));
+        cache.put(Endpoint::Airport, "LHR", &1);
+        cache.put(Endpoint::Autocomplete, "lon", &2);
+        cache.clear().unwrap();
+        assert_eq!(cache.get::<u32>(Endpoint::Airport, "LHR"), None);
+        assert!(!dir.path().join("airport").exists());
+    }
+}
@@ -0,0 +1,371 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use clap::builder::NonEmptyStringValueParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ita_matrix::model::common::{Cabin, Pax};
+use ita_matrix::model::money::Money;
+use ita_matrix::model::routing::{ExtensionCode, RoutingCode};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "ita-matrix",
+    version,
+    about = "Query the ITA Matrix flight search API"
+)]
+pub struct Cli {
+    /// API key to use instead of the built-in one.
+    #[arg(long, env = "ITA_API_KEY", hide_env_values = true, global = true)]
+    pub api_key: Option<String>,
+
+    /// Cache reference-data lookups under this directory.
+    #[arg(long, env = "ITA_CACHE_DIR", global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Ignore cached entries and fetch fresh ones.
+    #[arg(long, global = true)]
+    pub refresh: bool,
+
+    /// Output format; defaults to a table on a terminal and JSON otherwise.
+    #[arg(long, value_enum, global = true)]
+    pub format: Option<Format>,
+
+    /// Disable colors in table output. Also honors `NO_COLOR`.
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Maximum table width; defaults to `COLUMNS` on a terminal.
+    #[arg(long, global = true)]
+    pub width: Option<usize>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Search for flights on specific dates.
+    Search(SearchArgs),
+    /// Search a range of dates for the lowest fares.
+    Calendar(CalendarArgs),
+    /// Run the saved searches in a TOML or JSON file.
+    Run {
+        file: PathBuf,
+        /// Only the searches with these names.
+        #[arg(long, value_delimiter = ',')]
+        name: Vec<String>,
+        /// Resolve relative dates against this day instead of today.
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        today: Option<NaiveDate>,
+    },
+    /// Booking details for one solution of an earlier search.
+    Details(SolutionArgs),
+    /// Fare rules for one solution of an earlier search.
+    Rules {
+        #[command(flatten)]
+        solution: SolutionArgs,
+        /// Index into the ticket's pricings.
+        #[arg(long, default_value_t = 0)]
+        pricing: u32,
+        /// Slice index (0 outbound, 1 return).
+        #[arg(long, default_value_t = 0)]
+        slice: u32,
+    },
+    /// Compare two saved search results for the same query: JSON search
+    /// responses or solution lists.
+    Diff { before: PathBuf, after: PathBuf },
+    /// Track fares for saved searches over time.
+    Watch {
+        /// Watch database; defaults to `watch.redb` in the user data directory.
+        #[arg(long, env = "ITA_WATCH_DB")]
+        db: Option<PathBuf>,
+        #[command(subcommand)]
+        command: WatchCommand,
+    },
+    /// Complete a partial airport or city name.
+    Autocomplete {
+        #[arg(value_parser = NonEmptyStringValueParser::new())]
+        query: String,
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Look up an airport or multi-airport city by code.
+    Airport {
+        #[arg(value_parser = NonEmptyStringValueParser::new())]
+        code: String,
+    },
+    /// List the currencies prices can be shown in.
+    Currencies,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WatchCommand {
+    /// Watch the saved searches in a TOML or JSON file, one watch each.
+    Add {
+        file: PathBuf,
+        /// Only the searches with these names.
+        #[arg(long, value_delimiter = ',')]
+        name: Vec<String>,
+        /// Time between runs, e.g. 30m, 6h or 1d.
+        #[arg(long, default_value = "6h", value_parser = parse_interval)]
+        every: Duration,
+        /// How many of the cheapest solutions to keep per run.
+        #[arg(long, default_value_t = ita_matrix::watch::DEFAULT_TOP_N)]
+        top: usize,
+        /// Alert when the cheapest price drops to or below this, e.g. USD300.
+        #[arg(long)]
+        below: Option<Money>,
+        /// Alert when the cheapest price is the lowest seen so far.
+        #[arg(long)]
+        new_low: bool,
+        /// Alert when the cheapest price falls at least this many percent
+        /// since the previous run.
+        #[arg(long, value_name = "PERCENT")]
+        drop: Option<Decimal>,
+    },
+    /// List watches with their latest price.
+    List,
+    /// Delete a watch and its history.
+    Remove { name: String },
+    /// Run the watches that are due.
+    Run {
+        /// Run every watch, due or not.
+        #[arg(long)]
+        all: bool,
+        /// Keep running, sleeping until the next watch is due.
+        #[arg(long = "loop")]
+        repeat: bool,
+        /// Send alerts to the webhooks, commands and mailboxes in this
+        /// TOML or JSON file.
+        #[arg(long, env = "ITA_NOTIFY")]
+        notify: Option<PathBuf>,
+    },
+    /// Price history of one watch, oldest first.
+    History {
+        name: String,
+        /// Only the most recent runs.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Origin airport or city codes, comma-separated.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        requires = "to",
+        conflicts_with = "slices"
+    )]
+    pub from: Vec<String>,
+
+    /// Destination airport or city codes, comma-separated.
+    #[arg(long, value_delimiter = ',', requires = "date")]
+    pub to: Vec<String>,
+
+    /// Departure date, YYYY-MM-DD.
+    #[arg(long, requires = "from")]
+    pub date: Option<String>,
+
+    /// Return date for a round trip.
+    #[arg(long = "return", requires = "date")]
+    pub return_date: Option<String>,
+
+    /// A slice as ORIGINS:DESTINATIONS:DATE, e.g. BOS,PVD:LAX:2025-06-01.
+    /// Repeat for multi-city trips.
+    #[arg(long = "slice", value_name = "SPEC", required_unless_present = "from")]
+    pub slices: Vec<SliceArg>,
+
+    #[arg(long, value_enum, default_value_t = CabinArg::Coach)]
+    pub cabin: CabinArg,
+
+    #[command(flatten)]
+    pub pax: PaxArgs,
+
+    /// Routing code applied to every slice, e.g. "C:AA+ X:ORD C:UA+".
+    #[arg(long)]
+    pub routing: Option<RoutingCode>,
+
+    /// Extension code applied to every slice, e.g. "MAXSTOPS 1; -REDEYES".
+    #[arg(long)]
+    pub extension: Option<ExtensionCode>,
+
+    /// Maximum extra stops relative to the fewest available.
+    #[arg(long)]
+    pub max_stops: Option<u32>,
+
+    /// Sort order, e.g. "default", "price" or "duration".
+    #[arg(long)]
+    pub sort: Option<String>,
+
+    #[arg(long, default_value_t = 25)]
+    pub page_size: u32,
+
+    /// Print a link that opens the search in the Matrix web UI to stderr.
+    #[arg(long)]
+    pub matrix_link: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CalendarArgs {
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub from: Vec<String>,
+
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub to: Vec<String>,
+
+    /// First departure date, YYYY-MM-DD.
+    #[arg(long)]
+    pub start: String,
+
+    /// Last departure date, YYYY-MM-DD.
+    #[arg(long)]
+    pub end: String,
+
+    /// First return date; makes the search a round trip.
+    #[arg(long, requires = "return_end")]
+    pub return_start: Option<String>,
+
+    #[arg(long, requires = "return_start")]
+    pub return_end: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct SolutionArgs {
+    /// `solutionSet` from the search response.
+    #[arg(long)]
+    pub solution_set: String,
+
+    /// `session` from the search response.
+    #[arg(long)]
+    pub session: String,
+
+    /// Solution id from the search response.
+    #[arg(long)]
+    pub solution: String,
+}
+
+#[derive(Debug, Args)]
+pub struct PaxArgs {
+    #[arg(long, default_value_t = 1)]
+    pub adults: u8,
+    #[arg(long, default_value_t = 0)]
+    pub children: u8,
+    #[arg(long, default_value_t = 0)]
+    pub seniors: u8,
+    #[arg(long, default_value_t = 0)]
+    pub youth: u8,
+    #[arg(long, default_value_t = 0)]
+    pub infants_in_lap: u8,
+    #[arg(long, default_value_t = 0)]
+    pub infants_in_seat: u8,
+}
+
+impl From<PaxArgs> for Pax {
+    fn from(pax: PaxArgs) -> Self {
+        Self {
+            adults: pax.adults,
+            children: pax.children,
+            infants_in_lap: pax.infants_in_lap,
+            infants_in_seat: pax.infants_in_seat,
+            seniors: pax.seniors,
+            youth: pax.youth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    /// One row per slice.
+    Csv,
+    /// One JSON object per slice, one per line.
+    Jsonl,
+    /// CSV with one row per flight.
+    Segments,
+    /// iCalendar, one event per flight; details only.
+    Ics,
+    /// Plain-text itinerary for email; details only.
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CabinArg {
+    Coach,
+    PremiumCoach,
+    Business,
+    First,
+}
+
+impl From<CabinArg> for Cabin {
+    fn from(cabin: CabinArg) -> Self {
+        match cabin {
+            CabinArg::Coach => Cabin::Coach,
+            CabinArg::PremiumCoach => Cabin::PremiumCoach,
+            CabinArg::Business => Cabin::Business,
+            CabinArg::First => Cabin::First,
+        }
+    }
+}
+
+/// `90s`, `30m`, `6h`, `1d`; a bare number is minutes.
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (count, unit) = s.split_at(split);
+    let count: u64 = count
+        .parse()
+        .map_err(|_| format!("expected a number and a unit, got {s:?}"))?;
+    let unit_secs: u64 = match unit {
+        "s" => 1,
+        "" | "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("unknown unit {unit:?}; use s, m, h or d")),
+    };
+    let secs = count
+        .checked_mul(unit_secs)
+        .ok_or_else(|| format!("interval {s:?} is too long"))?;
+    if secs == 0 {
+        return Err("the interval must be positive".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+#[derive(Debug, Clone)]
+pub struct SliceArg {
+    pub origins: Vec<String>,
+    pub destinations: Vec<String>,
+    pub date: String,
+}
+
+impl FromStr for SliceArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let [origins, destinations, date] = parts[..] else {
+            return Err(format!("expected ORIGINS:DESTINATIONS:DATE, got {s:?}"));
+        };
+        let codes = |list: &str| -> Vec<String> {
+            list.split(',')
+                .map(|c| c.trim().to_ascii_uppercase())
+                .filter(|c| !c.is_empty())
+                .collect()
+        };
+        let (origins, destinations) = (codes(origins), codes(destinations));
+        if origins.is_empty() || destinations.is_empty() || date.is_empty() {
+            return Err(format!("empty origins, destinations or date in {s:?}"));
+        }
+        Ok(Self {
+            origins,
+            destinations,
+            date: date.to_string(),
+        })
+    }
+}
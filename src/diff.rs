@@ -0,0 +1,420 @@
+//! Compare two results for the same query: which itineraries appeared or
+//! disappeared, which changed price, and how the cheapest price moved.
+//!
+//! Solution ids are only meaningful within one session, so itineraries are
+//! matched by their flights instead: carrier, flight number and departure
+//! date of every segment, slice by slice.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Serialize, Serializer};
+
+use crate::model::money::Money;
+use crate::model::routing::SliceView;
+use crate::model::search::{SearchResponse, Solution, SolutionSlice};
+use crate::watch::percent_change;
+
+/// One flight: `AA1234/2026-02-15`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FlightKey {
+    pub carrier: String,
+    pub number: u32,
+    pub date: Option<NaiveDate>,
+}
+
+impl Display for FlightKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.carrier, self.number)?;
+        match self.date {
+            Some(date) => write!(f, "/{date}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The flights of an itinerary, slice by slice. Displays as
+/// `AA1234/2026-02-15 AA567/2026-02-15 | AA88/2026-02-20`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signature(pub Vec<Vec<FlightKey>>);
+
+impl Signature {
+    /// `None` when a flight lacks its carrier or number. Without segment
+    /// details, the slice's departure date stands in for each flight's.
+    pub fn of(solution: &Solution) -> Option<Self> {
+        let slices = solution.slices();
+        if slices.is_empty() {
+            return None;
+        }
+        slices
+            .iter()
+            .map(slice_keys)
+            .collect::<Option<_>>()
+            .map(Self)
+    }
+}
+
+fn slice_keys(slice: &SolutionSlice) -> Option<Vec<FlightKey>> {
+    let view = SliceView::from(slice);
+    if view.legs.is_empty() {
+        return None;
+    }
+    let slice_date = date(slice.departure.as_deref());
+    view.legs
+        .iter()
+        .enumerate()
+        .map(|(i, leg)| {
+            let segment_date = slice
+                .segments
+                .get(i)
+                .and_then(|s| date(s.departure.as_deref()));
+            Some(FlightKey {
+                carrier: leg.carrier.as_deref()?.trim().to_string(),
+                number: leg.number?,
+                date: segment_date.or(slice_date),
+            })
+        })
+        .collect()
+}
+
+/// The date of `2026-02-15T06:00-07:00`.
+fn date(time: Option<&str>) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(time?.get(..10)?, "%Y-%m-%d").ok()
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, slice) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            for (j, flight) in slice.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{flight}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A price before and after, and the difference when both are in the
+/// same currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PriceDelta {
+    pub before: Option<Money>,
+    pub after: Option<Money>,
+    pub amount: Option<Money>,
+    pub percent: Option<Decimal>,
+}
+
+impl PriceDelta {
+    pub fn new(before: Option<Money>, after: Option<Money>) -> Self {
+        let (amount, percent) = match (before, after) {
+            (Some(b), Some(a)) => (a.checked_sub(b).ok(), percent_change(b, a)),
+            _ => (None, None),
+        };
+        Self {
+            before,
+            after,
+            amount,
+            percent,
+        }
+    }
+
+    pub fn is_change(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// An itinerary present in only one of the results.
+#[derive(Debug, Clone, Serialize)]
+pub struct Itinerary {
+    pub signature: Signature,
+    pub solution_id: String,
+    pub price: Option<Money>,
+}
+
+/// An itinerary in both results at different prices.
+#[derive(Debug, Clone, Serialize)]
+pub struct Repriced {
+    pub signature: Signature,
+    pub before_id: String,
+    pub after_id: String,
+    pub price: PriceDelta,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultDiff {
+    /// In the later result only, in its order.
+    pub appeared: Vec<Itinerary>,
+    /// In the earlier result only, in its order.
+    pub disappeared: Vec<Itinerary>,
+    /// In both at different prices, in the later result's order.
+    pub repriced: Vec<Repriced>,
+    /// In both at the same price.
+    pub unchanged: usize,
+    /// Solutions whose flights could not be identified; left out above.
+    pub unmatched: usize,
+    /// Cheapest price across each whole result; absent for a result priced
+    /// in more than one currency.
+    pub cheapest: PriceDelta,
+}
+
+pub fn diff_responses(before: &SearchResponse, after: &SearchResponse) -> ResultDiff {
+    diff_solutions(solutions(before), solutions(after))
+}
+
+fn solutions(response: &SearchResponse) -> &[Solution] {
+    response
+        .solution_list
+        .as_ref()
+        .map(|list| list.solutions.as_slice())
+        .unwrap_or_default()
+}
+
+/// Match the two lists by [`Signature`]. When one list has the same
+/// flights more than once, only the cheapest counts.
+pub fn diff_solutions(before: &[Solution], after: &[Solution]) -> ResultDiff {
+    let (old, old_order, old_unmatched) = index(before);
+    let (new, new_order, new_unmatched) = index(after);
+
+    let mut diff = ResultDiff {
+        appeared: Vec::new(),
+        disappeared: Vec::new(),
+        repriced: Vec::new(),
+        unchanged: 0,
+        unmatched: old_unmatched + new_unmatched,
+        cheapest: PriceDelta::new(cheapest(before), cheapest(after)),
+    };
+    for signature in &new_order {
+        let now = new[signature];
+        match old.get(signature) {
+            None => diff.appeared.push(itinerary(signature, now)),
+            Some(was) => {
+                let price = PriceDelta::new(was.price(), now.price());
+                if price.is_change() {
+                    diff.repriced.push(Repriced {
+                        signature: signature.clone(),
+                        before_id: was.id.clone(),
+                        after_id: now.id.clone(),
+                        price,
+                    });
+                } else {
+                    diff.unchanged += 1;
+                }
+            }
+        }
+    }
+    for signature in &old_order {
+        if !new.contains_key(signature) {
+            diff.disappeared.push(itinerary(signature, old[signature]));
+        }
+    }
+    diff
+}
+
+/// The cheapest solution per signature, the signatures in first-seen
+/// order, and how many solutions had none.
+fn index(solutions: &[Solution]) -> (HashMap<Signature, &Solution>, Vec<Signature>, usize) {
+    let mut best: HashMap<Signature, &Solution> = HashMap::new();
+    let mut order = Vec::new();
+    let mut unmatched = 0;
+    for solution in solutions {
+        let Some(signature) = Signature::of(solution) else {
+            unmatched += 1;
+            continue;
+        };
+        match best.get(&signature) {
+            None => {
+                order.push(signature.clone());
+                best.insert(signature, solution);
+            }
+            Some(seen) if is_cheaper(solution, seen) => {
+                best.insert(signature, solution);
+            }
+            Some(_) => {}
+        }
+    }
+    (best, order, unmatched)
+}
+
+fn is_cheaper(a: &Solution, b: &Solution) -> bool {
+    match (a.price(), b.price()) {
+        (Some(a), Some(b)) => a.currency() == b.currency() && a < b,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// The lowest price, or `None` when there are no prices or they are in more
+/// than one currency.
+fn cheapest(solutions: &[Solution]) -> Option<Money> {
+    Money::try_min(solutions.iter().filter_map(Solution::price))
+        .ok()
+        .flatten()
+}
+
+fn itinerary(signature: &Signature, solution: &Solution) -> Itinerary {
+    Itinerary {
+        signature: signature.clone(),
+        solution_id: solution.id.clone(),
+        price: solution.price(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(id: &str, price: &str, flights: &[&str], date: &str) -> Solution {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "displayTotal": price,
+            "itinerary": { "slices": [{
+                "origin": { "code": "PHX" },
+                "destination": { "code": "DTW" },
+                "departure": format!("{date}T06:00-07:00"),
+                "flights": flights
+            }]}
+        }))
+        .unwrap()
+    }
+
+    fn money(s: &str) -> Money {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn signature_from_flights_and_segments() {
+        let summary = solution("a", "USD1.00", &["AA1234", "AA 0567"], "2026-02-15");
+        assert_eq!(
+            Signature::of(&summary).unwrap().to_string(),
+            "AA1234/2026-02-15 AA567/2026-02-15"
+        );
+
+        let detailed: Solution = serde_json::from_value(serde_json::json!({
+            "id": "b",
+            "itinerary": { "slices": [
+                { "departure": "2026-02-15T23:00-07:00", "segments": [
+                    { "carrier": { "code": "AA" }, "flightNumber": "1234",
+                      "departure": "2026-02-15T23:00-07:00" },
+                    { "carrier": { "code": "AA" }, "flightNumber": "567",
+                      "departure": "2026-02-16T06:10-05:00" }
+                ]},
+                { "departure": "2026-02-20T08:00-05:00", "flights": ["DL88"] }
+            ]}
+        }))
+        .unwrap();
+        assert_eq!(
+            Signature::of(&detailed).unwrap().to_string(),
+            "AA1234/2026-02-15 AA567/2026-02-16 | DL88/2026-02-20"
+        );
+
+        let bare = solution("c", "USD1.00", &[], "2026-02-15");
+        assert_eq!(Signature::of(&bare), None);
+    }
+
+    #[test]
+    fn matches_by_flights_not_ids() {
+        let before = [
+            solution("s1-a", "USD300.00", &["AA1"], "2026-02-15"),
+            solution("s1-b", "USD250.00", &["DL2"], "2026-02-15"),
+            solution("s1-c", "USD400.00", &["UA3"], "2026-02-15"),
+            solution("s1-d", "USD500.00", &["AA1"], "2026-02-16"),
+        ];
+        let after = [
+            solution("s2-a", "USD230.00", &["DL2"], "2026-02-15"),
+            solution("s2-b", "USD300.00", &["AA1"], "2026-02-15"),
+            solution("s2-c", "USD199.00", &["B64"], "2026-02-15"),
+            solution("s2-d", "USD520.00", &["AA1"], "2026-02-16"),
+            solution("s2-e", "USD1.00", &[], "2026-02-15"),
+        ];
+        let diff = diff_solutions(&before, &after);
+
+        let ids = |items: &[Itinerary]| -> Vec<String> {
+            items.iter().map(|i| i.solution_id.clone()).collect()
+        };
+        assert_eq!(ids(&diff.appeared), ["s2-c"]);
+        assert_eq!(ids(&diff.disappeared), ["s1-c"]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.unmatched, 1);
+
+        let repriced: Vec<(&str, Option<Money>)> = diff
+            .repriced
+            .iter()
+            .map(|r| (r.before_id.as_str(), r.price.amount))
+            .collect();
+        assert_eq!(
+            repriced,
+            [
+                ("s1-b", Some(money("USD-20.00"))),
+                ("s1-d", Some(money("USD20.00")))
+            ]
+        );
+        assert_eq!(diff.repriced[0].price.percent, Some(Decimal::new(-800, 2)));
+
+        // The unmatched solution still counts towards the cheapest price.
+        assert_eq!(diff.cheapest.before, Some(money("USD250.00")));
+        assert_eq!(diff.cheapest.after, Some(money("USD1.00")));
+    }
+
+    #[test]
+    fn duplicate_flights_keep_the_cheapest() {
+        let before = [solution("a", "USD300.00", &["AA1"], "2026-02-15")];
+        let after = [
+            solution("b", "USD320.00", &["AA1"], "2026-02-15"),
+            solution("c", "USD280.00", &["AA1"], "2026-02-15"),
+        ];
+        let diff = diff_solutions(&before, &after);
+        assert_eq!(diff.repriced.len(), 1);
+        assert_eq!(diff.repriced[0].after_id, "c");
+        assert_eq!(diff.cheapest.amount, Some(money("USD-20.00")));
+    }
+
+    #[test]
+    fn currencies_do_not_mix() {
+        let before = [solution("a", "USD300.00", &["AA1"], "2026-02-15")];
+        let after = [solution("b", "EUR280.00", &["AA1"], "2026-02-15")];
+        let diff = diff_solutions(&before, &after);
+        assert_eq!(diff.repriced[0].price.amount, None);
+        assert_eq!(diff.repriced[0].price.percent, None);
+        assert_eq!(diff.cheapest.amount, None);
+
+        let mixed = [
+            solution("b", "EUR280.00", &["AA1"], "2026-02-15"),
+            solution("c", "USD250.00", &["DL2"], "2026-02-15"),
+        ];
+        let diff = diff_solutions(&before, &mixed);
+        assert_eq!(diff.cheapest.before, Some(money("USD300.00")));
+        assert_eq!(diff.cheapest.after, None);
+    }
+
+    #[test]
+    fn responses_and_json() {
+        let response = |solutions: serde_json::Value| -> SearchResponse {
+            serde_json::from_value(
+                serde_json::json!({ "solutionList": { "solutions": solutions } }),
+            )
+            .unwrap()
+        };
+        let before = response(serde_json::json!([]));
+        let after = response(
+            serde_json::to_value([solution("x", "USD99.00", &["WN7"], "2026-03-01")]).unwrap(),
+        );
+        let diff = diff_responses(&before, &after);
+        let value = serde_json::to_value(&diff).unwrap();
+        assert_eq!(value["appeared"][0]["signature"], "WN7/2026-03-01");
+        assert_eq!(value["cheapest"]["before"], serde_json::Value::Null);
+        assert_eq!(value["cheapest"]["after"], "USD99.00");
+    }
+}
@@ -19,6 +19,9 @@ pub enum ItaError {
 
     #[error("missing field in response: {field}")]
     MissingField { field: &'static str },
+
+    #[error("{0} must not be empty")]
+    EmptyArgument(&'static str),
 }
 
 pub type Result<T> = std::result::Result<T, ItaError>;
@@ -0,0 +1,541 @@
+//! Flat exports of search results and booking details for spreadsheets and
+//! data frames.
+//!
+//! Every record carries [`SCHEMA_VERSION`]. Columns are only ever appended
+//! within a version; renaming, removing or reordering one bumps it.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::model::response::{Airport, BookingDetails, ItinerarySlice, Segment};
+use crate::model::routing::SliceView;
+use crate::model::search::{Solution, SolutionSlice};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One [`SliceRecord`] per row.
+    Csv,
+    /// One [`SliceRecord`] per line.
+    Jsonl,
+    /// One [`SegmentRecord`] per CSV row.
+    Segments,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "segments" => Ok(Self::Segments),
+            _ => Err(format!("unknown export format: {s:?}")),
+        }
+    }
+}
+
+/// A flat export record with a fixed column order.
+pub trait Record: Serialize {
+    /// Column names in serialization order, written as the CSV header even
+    /// when there are no records.
+    const COLUMNS: &'static [&'static str];
+}
+
+/// One slice of a solution. Prices are repeated on every slice of the
+/// solution; multi-valued fields are joined with spaces.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SliceRecord {
+    pub schema_version: u32,
+    pub solution_id: String,
+    pub slice_index: usize,
+    /// Total for all passengers, as a decimal string.
+    pub price: Option<String>,
+    pub currency: Option<String>,
+    pub carriers: String,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    /// As returned by the API, with the local UTC offset.
+    pub departure: Option<String>,
+    pub arrival: Option<String>,
+    pub duration_minutes: Option<u32>,
+    pub stops: usize,
+    pub via: String,
+    pub flights: String,
+    pub warnings: Option<String>,
+}
+
+/// One flight of a slice. Times, aircraft and booking class are empty when
+/// the source only summarizes the slice.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentRecord {
+    pub schema_version: u32,
+    pub solution_id: String,
+    pub slice_index: usize,
+    pub segment_index: usize,
+    pub carrier: Option<String>,
+    pub flight_number: Option<String>,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub departure: Option<String>,
+    pub arrival: Option<String>,
+    pub duration_minutes: Option<u32>,
+    pub aircraft: Option<String>,
+    pub booking_code: Option<String>,
+    pub cabin: Option<String>,
+    /// Layover after this flight.
+    pub connection_minutes: Option<u32>,
+}
+
+impl Record for SliceRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "schema_version",
+        "solution_id",
+        "slice_index",
+        "price",
+        "currency",
+        "carriers",
+        "origin",
+        "destination",
+        "departure",
+        "arrival",
+        "duration_minutes",
+        "stops",
+        "via",
+        "flights",
+        "warnings",
+    ];
+}
+
+impl Record for SegmentRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "schema_version",
+        "solution_id",
+        "slice_index",
+        "segment_index",
+        "carrier",
+        "flight_number",
+        "origin",
+        "destination",
+        "departure",
+        "arrival",
+        "duration_minutes",
+        "aircraft",
+        "booking_code",
+        "cabin",
+        "connection_minutes",
+    ];
+}
+
+pub fn slice_records(solutions: &[Solution]) -> Vec<SliceRecord> {
+    let mut records = Vec::new();
+    for solution in solutions {
+        let price = solution.price();
+        let carriers: Vec<&str> = solution
+            .carriers()
+            .into_iter()
+            .filter_map(|c| c.code.as_deref())
+            .collect();
+        for (i, slice) in solution.slices().iter().enumerate() {
+            records.push(SliceRecord {
+                schema_version: SCHEMA_VERSION,
+                solution_id: solution.id.clone(),
+                slice_index: i,
+                price: price.map(|p| p.amount().to_string()),
+                currency: price.map(|p| p.currency().to_string()),
+                carriers: carriers.join(" "),
+                origin: code(slice.origin.as_ref()),
+                destination: code(slice.destination.as_ref()),
+                departure: slice.departure.clone(),
+                arrival: slice.arrival.clone(),
+                duration_minutes: slice.duration,
+                stops: slice.stop_count(),
+                via: joined(slice.stops.iter().filter_map(|a| a.code.as_deref())),
+                flights: flights(slice),
+                warnings: slice.ext.as_ref().and_then(|e| e.warnings.clone()),
+            });
+        }
+    }
+    records
+}
+
+/// Segments of every solution, falling back to the `flights` summary when a
+/// slice has no segments.
+pub fn segment_records(solutions: &[Solution]) -> Vec<SegmentRecord> {
+    let mut records = Vec::new();
+    for solution in solutions {
+        for (i, slice) in solution.slices().iter().enumerate() {
+            if !slice.segments.is_empty() {
+                records.extend(segments(&solution.id, i, &slice.segments));
+                continue;
+            }
+            let view = SliceView::from(slice);
+            records.extend(
+                view.legs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, leg)| SegmentRecord {
+                        schema_version: SCHEMA_VERSION,
+                        solution_id: solution.id.clone(),
+                        slice_index: i,
+                        segment_index: j,
+                        carrier: leg.carrier,
+                        flight_number: leg.number.map(|n| n.to_string()),
+                        origin: leg.origin,
+                        destination: leg.destination,
+                        departure: None,
+                        arrival: None,
+                        duration_minutes: None,
+                        aircraft: None,
+                        booking_code: None,
+                        cabin: None,
+                        connection_minutes: None,
+                    }),
+            );
+        }
+    }
+    records
+}
+
+/// Booking details of one solution as slice records. Slice times come from
+/// the first and last segment.
+pub fn booking_slice_records(solution_id: &str, details: &BookingDetails) -> Vec<SliceRecord> {
+    let price = details.ext.as_ref().and_then(|e| e.total_price.or(e.price));
+    let slices = booking_slices(details);
+    slices
+        .iter()
+        .enumerate()
+        .map(|(i, slice)| {
+            let (first, last) = (slice.segments.first(), slice.segments.last());
+            let mut carriers: Vec<&str> = Vec::new();
+            for c in slice
+                .segments
+                .iter()
+                .filter_map(|s| s.carrier.as_ref()?.code.as_deref())
+            {
+                if !carriers.contains(&c) {
+                    carriers.push(c);
+                }
+            }
+            SliceRecord {
+                schema_version: SCHEMA_VERSION,
+                solution_id: solution_id.to_string(),
+                slice_index: i,
+                price: price.map(|p| p.amount().to_string()),
+                currency: price.map(|p| p.currency().to_string()),
+                carriers: carriers.join(" "),
+                origin: code(slice.origin.as_ref()),
+                destination: code(slice.destination.as_ref()),
+                departure: first.and_then(|s| s.departure.clone()),
+                arrival: last.and_then(|s| s.arrival.clone()),
+                duration_minutes: slice.duration,
+                stops: slice.segments.len().saturating_sub(1),
+                via: joined(
+                    slice
+                        .segments
+                        .iter()
+                        .skip(1)
+                        .filter_map(|s| s.origin.as_ref()?.code.as_deref()),
+                ),
+                flights: joined(slice.segments.iter().map(flight)),
+                warnings: slice.ext.as_ref().and_then(|e| e.warnings.clone()),
+            }
+        })
+        .collect()
+}
+
+pub fn booking_segment_records(solution_id: &str, details: &BookingDetails) -> Vec<SegmentRecord> {
+    booking_slices(details)
+        .iter()
+        .enumerate()
+        .flat_map(|(i, slice)| segments(solution_id, i, &slice.segments))
+        .collect()
+}
+
+/// Write a solution list in `format`.
+pub fn export_solutions<W: Write>(
+    solutions: &[Solution],
+    format: ExportFormat,
+    writer: W,
+) -> Result<(), ExportError> {
+    match format {
+        ExportFormat::Csv => write_csv(writer, &slice_records(solutions)),
+        ExportFormat::Jsonl => write_jsonl(writer, &slice_records(solutions)),
+        ExportFormat::Segments => write_csv(writer, &segment_records(solutions)),
+    }
+}
+
+/// Write the booking details of one solution in `format`.
+pub fn export_booking_details<W: Write>(
+    solution_id: &str,
+    details: &BookingDetails,
+    format: ExportFormat,
+    writer: W,
+) -> Result<(), ExportError> {
+    match format {
+        ExportFormat::Csv => write_csv(writer, &booking_slice_records(solution_id, details)),
+        ExportFormat::Jsonl => write_jsonl(writer, &booking_slice_records(solution_id, details)),
+        ExportFormat::Segments => write_csv(writer, &booking_segment_records(solution_id, details)),
+    }
+}
+
+/// Records as CSV with a header row, which is written even for an empty
+/// list.
+pub fn write_csv<W: Write, R: Record>(writer: W, records: &[R]) -> Result<(), ExportError> {
+    let mut csv = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    csv.write_record(R::COLUMNS)?;
+    for record in records {
+        csv.serialize(record)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+pub fn write_jsonl<W: Write, R: Serialize>(
+    mut writer: W,
+    records: &[R],
+) -> Result<(), ExportError> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn booking_slices(details: &BookingDetails) -> &[ItinerarySlice] {
+    details
+        .itinerary
+        .as_ref()
+        .map(|it| it.slices.as_slice())
+        .unwrap_or_default()
+}
+
+fn segments<'a>(
+    solution_id: &'a str,
+    slice_index: usize,
+    segments: &'a [Segment],
+) -> impl Iterator<Item = SegmentRecord> + 'a {
+    segments.iter().enumerate().map(move |(j, s)| {
+        let booking = s.booking_infos.first();
+        SegmentRecord {
+            schema_version: SCHEMA_VERSION,
+            solution_id: solution_id.to_string(),
+            slice_index,
+            segment_index: j,
+            carrier: s.carrier.as_ref().and_then(|c| c.code.clone()),
+            flight_number: s.flight_number.clone(),
+            origin: code(s.origin.as_ref()),
+            destination: code(s.destination.as_ref()),
+            departure: s.departure.clone(),
+            arrival: s.arrival.clone(),
+            duration_minutes: s.duration,
+            aircraft: s.aircraft.as_ref().and_then(|a| a.code.clone()),
+            booking_code: booking.and_then(|b| b.booking_code.clone()),
+            cabin: booking.and_then(|b| b.cabin.clone()),
+            connection_minutes: s.connection.as_ref().and_then(|c| c.duration),
+        }
+    })
+}
+
+fn flights(slice: &SolutionSlice) -> String {
+    if slice.flights.is_empty() {
+        joined(slice.segments.iter().map(flight))
+    } else {
+        slice.flights.join(" ")
+    }
+}
+
+fn flight(segment: &Segment) -> String {
+    format!(
+        "{}{}",
+        segment
+            .carrier
+            .as_ref()
+            .and_then(|c| c.code.as_deref())
+            .unwrap_or_default(),
+        segment.flight_number.as_deref().unwrap_or_default()
+    )
+}
+
+fn code(airport: Option<&Airport>) -> Option<String> {
+    airport.and_then(|a| a.code.clone())
+}
+
+fn joined<S: AsRef<str>>(items: impl Iterator<Item = S>) -> String {
+    items
+        .map(|s| s.as_ref().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solutions() -> Vec<Solution> {
+        serde_json::from_value(serde_json::json!([{
+            "id": "abc123",
+            "displayTotal": "USD224.20",
+            "itinerary": {
+                "carriers": [{ "code": "AA" }],
+                "slices": [{
+                    "origin": { "code": "PHX" },
+                    "destination": { "code": "DTW" },
+                    "departure": "2026-02-15T06:00-07:00",
+                    "arrival": "2026-02-15T15:55-05:00",
+                    "duration": 415,
+                    "flights": ["AA1234", "AA567"],
+                    "stops": [{ "code": "DFW" }],
+                    "ext": { "warnings": "Long layover, \"tight\" connection" }
+                }]
+            }
+        }]))
+        .unwrap()
+    }
+
+    fn details() -> BookingDetails {
+        serde_json::from_value(serde_json::json!({
+            "ext": { "totalPrice": "USD224.20" },
+            "itinerary": { "slices": [{
+                "origin": { "code": "PHX" },
+                "destination": { "code": "DTW" },
+                "duration": 415,
+                "segments": [
+                    {
+                        "carrier": { "code": "AA" }, "flightNumber": "1234",
+                        "origin": { "code": "PHX" }, "destination": { "code": "DFW" },
+                        "departure": "2026-02-15T06:00-07:00",
+                        "arrival": "2026-02-15T10:40-05:00",
+                        "aircraft": { "code": "321" },
+                        "bookingInfos": [{ "bookingCode": "O", "cabin": "COACH" }],
+                        "connection": { "duration": 75 }
+                    },
+                    {
+                        "carrier": { "code": "AA" }, "flightNumber": "567",
+                        "origin": { "code": "DFW" }, "destination": { "code": "DTW" },
+                        "arrival": "2026-02-15T15:55-05:00"
+                    }
+                ]
+            }]}
+        }))
+        .unwrap()
+    }
+
+    fn to_string(f: impl FnOnce(&mut Vec<u8>) -> Result<(), ExportError>) -> String {
+        let mut out = Vec::new();
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_columns_are_stable() {
+        let out = to_string(|w| export_solutions(&solutions(), ExportFormat::Csv, w));
+        let mut lines = out.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "schema_version,solution_id,slice_index,price,currency,carriers,origin,\
+                 destination,departure,arrival,duration_minutes,stops,via,flights,warnings"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "1,abc123,0,224.20,USD,AA,PHX,DTW,2026-02-15T06:00-07:00,\
+                 2026-02-15T15:55-05:00,415,1,DFW,AA1234 AA567,\
+                 \"Long layover, \"\"tight\"\" connection\""
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn empty_csv_has_header() {
+        let out = to_string(|w| export_solutions(&[], ExportFormat::Csv, w));
+        assert_eq!(out, format!("{}\n", SliceRecord::COLUMNS.join(",")));
+
+        let out = to_string(|w| export_solutions(&[], ExportFormat::Segments, w));
+        assert_eq!(out, format!("{}\n", SegmentRecord::COLUMNS.join(",")));
+    }
+
+    #[test]
+    fn columns_match_serialized_fields() {
+        fn fields(record: &impl Serialize) -> Vec<String> {
+            let mut csv = csv::Writer::from_writer(Vec::new());
+            csv.serialize(record).unwrap();
+            let out = String::from_utf8(csv.into_inner().unwrap()).unwrap();
+            out.lines()
+                .next()
+                .unwrap()
+                .split(',')
+                .map(String::from)
+                .collect()
+        }
+        let solutions = solutions();
+        assert_eq!(fields(&slice_records(&solutions)[0]), SliceRecord::COLUMNS);
+        assert_eq!(
+            fields(&segment_records(&solutions)[0]),
+            SegmentRecord::COLUMNS
+        );
+    }
+
+    #[test]
+    fn jsonl_one_object_per_line() {
+        let out = to_string(|w| export_solutions(&solutions(), ExportFormat::Jsonl, w));
+        let value: serde_json::Value = serde_json::from_str(out.trim_end()).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["price"], "224.20");
+        assert_eq!(value["stops"], 1);
+    }
+
+    #[test]
+    fn segments_fall_back_to_flight_summary() {
+        let records = segment_records(&solutions());
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].carrier.as_deref(), Some("AA"));
+        assert_eq!(records[1].flight_number.as_deref(), Some("567"));
+        assert_eq!(records[1].origin.as_deref(), Some("DFW"));
+        assert_eq!(records[1].departure, None);
+    }
+
+    #[test]
+    fn booking_details_rows() {
+        let slices = booking_slice_records("abc123", &details());
+        assert_eq!(slices[0].flights, "AA1234 AA567");
+        assert_eq!(slices[0].via, "DFW");
+        assert_eq!(slices[0].arrival.as_deref(), Some("2026-02-15T15:55-05:00"));
+
+        let out =
+            to_string(|w| export_booking_details("abc123", &details(), ExportFormat::Segments, w));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("schema_version,solution_id,slice_index,segment_index,"));
+        assert!(lines[1].ends_with(",321,O,COACH,75"));
+    }
+
+    #[test]
+    fn parses_format_names() {
+        assert_eq!("JSONL".parse(), Ok(ExportFormat::Jsonl));
+        assert_eq!("segments".parse(), Ok(ExportFormat::Segments));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! iCalendar (RFC 5545) export of [`BookingDetails`], one VEVENT per segment.
+//!
+//! Event times are written in the local time of the departure and arrival
+//! airports with a `TZID`, and each zone used gets a VTIMEZONE describing
+//! the offsets in effect over the trip. Airports whose zone is unknown fall
+//! back to UTC.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use thiserror::Error;
+
+use crate::model::location::Location;
+use crate::model::response::{Airport, BookingDetails, Segment};
+use crate::reference::AirportDb;
+
+#[derive(Debug, Error)]
+pub enum IcsError {
+    #[error("segment {segment} has no {field} time")]
+    MissingTime { segment: usize, field: &'static str },
+
+    #[error("segment {segment}: invalid time {value:?}")]
+    InvalidTime { segment: usize, value: String },
+
+    #[error("unknown time zone {timezone:?} for {code}")]
+    UnknownTimeZone { code: String, timezone: String },
+}
+
+/// Builds calendars. Time zones come from [`Location`]s given to
+/// [`Calendar::location`], then from the embedded airport table.
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    timezones: HashMap<String, String>,
+    stamp: Option<DateTime<Utc>>,
+}
+
+impl Calendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the location's `timezone` for its code.
+    #[must_use]
+    pub fn location(mut self, location: &Location) -> Self {
+        if let Some(tz) = &location.timezone {
+            self.timezones.insert(location.code.clone(), tz.clone());
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn timezone(mut self, code: &str, timezone: &str) -> Self {
+        self.timezones
+            .insert(code.to_ascii_uppercase(), timezone.to_string());
+        self
+    }
+
+    /// `DTSTAMP` for every event; defaults to the current time.
+    #[must_use]
+    pub fn stamp(mut self, stamp: DateTime<Utc>) -> Self {
+        self.stamp = Some(stamp);
+        self
+    }
+
+    pub fn render(&self, details: &BookingDetails) -> Result<String, IcsError> {
+        let stamp = self.stamp.unwrap_or_else(Utc::now);
+        let slices = details
+            .itinerary
+            .as_ref()
+            .map(|it| it.slices.as_slice())
+            .unwrap_or_default();
+
+        let mut events = Vec::new();
+        // Instants each zone must cover, for its VTIMEZONE.
+        let mut zones: BTreeMap<String, (Tz, Vec<DateTime<Utc>>)> = BTreeMap::new();
+        for (index, segment) in slices.iter().flat_map(|s| &s.segments).enumerate() {
+            let event = Event::new(index, segment)?;
+            for (airport, time) in [
+                (segment.origin.as_ref(), event.start),
+                (segment.destination.as_ref(), event.end),
+            ] {
+                if let Some(tz) = self.zone(airport)? {
+                    zones
+                        .entry(tz.name().to_string())
+                        .or_insert_with(|| (tz, Vec::new()))
+                        .1
+                        .push(time.with_timezone(&Utc));
+                }
+            }
+            events.push((event, segment));
+        }
+
+        let mut out = Lines::default();
+        out.push("BEGIN:VCALENDAR");
+        out.push("VERSION:2.0");
+        out.push(concat!(
+            "PRODID:-//ita-matrix//",
+            env!("CARGO_PKG_VERSION"),
+            "//EN"
+        ));
+        out.push("CALSCALE:GREGORIAN");
+        for (name, (tz, instants)) in &zones {
+            write_vtimezone(&mut out, name, *tz, instants);
+        }
+        for (event, segment) in &events {
+            let start_tz = self.zone(segment.origin.as_ref())?;
+            let end_tz = self.zone(segment.destination.as_ref())?;
+            event.write(&mut out, segment, stamp, start_tz, end_tz);
+        }
+        out.push("END:VCALENDAR");
+        Ok(out.0)
+    }
+
+    fn zone(&self, airport: Option<&Airport>) -> Result<Option<Tz>, IcsError> {
+        let Some(code) = airport.and_then(|a| a.code.as_deref()) else {
+            return Ok(None);
+        };
+        let name = match self.timezones.get(code) {
+            Some(name) => name.as_str(),
+            None => match AirportDb::embedded().get(code) {
+                Some(info) => info.timezone.as_str(),
+                None => return Ok(None),
+            },
+        };
+        name.parse()
+            .map(Some)
+            .map_err(|_| IcsError::UnknownTimeZone {
+                code: code.to_string(),
+                timezone: name.to_string(),
+            })
+    }
+}
+
+/// Shorthand for `Calendar::new().render(details)`.
+pub fn to_ics(details: &BookingDetails) -> Result<String, IcsError> {
+    Calendar::new().render(details)
+}
+
+struct Event {
+    index: usize,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+}
+
+impl Event {
+    fn new(index: usize, segment: &Segment) -> Result<Self, IcsError> {
+        let time = |value: Option<&String>, field| -> Result<DateTime<FixedOffset>, IcsError> {
+            let value = value.ok_or(IcsError::MissingTime {
+                segment: index,
+                field,
+            })?;
+            parse_time(value).ok_or_else(|| IcsError::InvalidTime {
+                segment: index,
+                value: value.clone(),
+            })
+        };
+        Ok(Self {
+            index,
+            start: time(segment.departure.as_ref(), "departure")?,
+            end: time(segment.arrival.as_ref(), "arrival")?,
+        })
+    }
+
+    fn write(
+        &self,
+        out: &mut Lines,
+        segment: &Segment,
+        stamp: DateTime<Utc>,
+        start_tz: Option<Tz>,
+        end_tz: Option<Tz>,
+    ) {
+        let carrier = segment.carrier.as_ref();
+        let code = carrier.and_then(|c| c.code.as_deref()).unwrap_or_default();
+        let number = segment.flight_number.as_deref().unwrap_or_default();
+        let origin = airport_code(segment.origin.as_ref());
+        let destination = airport_code(segment.destination.as_ref());
+
+        let mut description = Vec::new();
+        if let Some(name) = carrier.and_then(|c| c.name.as_deref().or(c.short_name.as_deref())) {
+            description.push(format!("{name} flight {number}"));
+        }
+        description.push(format!(
+            "{} to {}",
+            airport_name(segment.origin.as_ref()),
+            airport_name(segment.destination.as_ref())
+        ));
+        if let Some(aircraft) = segment
+            .aircraft
+            .as_ref()
+            .and_then(|a| a.name.as_deref().or(a.code.as_deref()))
+        {
+            description.push(format!("Aircraft: {aircraft}"));
+        }
+        for info in &segment.booking_infos {
+            let class = match (&info.booking_code, &info.cabin) {
+                (Some(code), Some(cabin)) => format!("{code} ({cabin})"),
+                (Some(code), None) => code.clone(),
+                (None, Some(cabin)) => cabin.clone(),
+                (None, None) => continue,
+            };
+            description.push(format!("Booking class: {class}"));
+        }
+
+        out.push("BEGIN:VEVENT");
+        out.push(&format!(
+            "UID:{code}{number}-{}-{origin}{destination}-{}@ita-matrix",
+            self.start.format("%Y%m%d"),
+            self.index
+        ));
+        out.push(&format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        out.push(&format!("DTSTART{}", date_time(self.start, start_tz)));
+        out.push(&format!("DTEND{}", date_time(self.end, end_tz)));
+        out.push(&format!(
+            "SUMMARY:{}",
+            escape(&format!("{code}{number} {origin}-{destination}"))
+        ));
+        out.push(&format!(
+            "LOCATION:{}",
+            escape(&airport_name(segment.origin.as_ref()))
+        ));
+        out.push(&format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        out.push("TRANSP:OPAQUE");
+        out.push("END:VEVENT");
+    }
+}
+
+/// `;TZID=<zone>:<local>` when the zone is known, else `:<utc>Z`.
+fn date_time(time: DateTime<FixedOffset>, tz: Option<Tz>) -> String {
+    match tz {
+        Some(tz) => format!(
+            ";TZID={}:{}",
+            tz.name(),
+            time.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ),
+        None => format!(":{}", time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")),
+    }
+}
+
+/// A VTIMEZONE with the offset in effect at the first instant, plus every
+/// transition between a day before the first and a day after the last.
+fn write_vtimezone(out: &mut Lines, name: &str, tz: Tz, instants: &[DateTime<Utc>]) {
+    let (Some(first), Some(last)) = (instants.iter().min(), instants.iter().max()) else {
+        return;
+    };
+    let (from, to) = (*first - Duration::days(1), *last + Duration::days(1));
+
+    out.push("BEGIN:VTIMEZONE");
+    out.push(&format!("TZID:{name}"));
+    let initial = tz.offset_from_utc_datetime(&from.naive_utc());
+    write_observance(out, &initial, &initial, "19700101T000000".to_string());
+
+    let mut at = from;
+    while at < to {
+        let next = (at + Duration::hours(12)).min(to);
+        if offset_at(tz, at) != offset_at(tz, next) {
+            let change = find_transition(tz, at, next);
+            let before = tz.offset_from_utc_datetime(&(change - Duration::minutes(1)).naive_utc());
+            let after = tz.offset_from_utc_datetime(&change.naive_utc());
+            // DTSTART of an observance is local time in the previous offset.
+            let onset = change.naive_utc() + Duration::seconds(i64::from(seconds(&before)));
+            write_observance(out, &before, &after, format_local(onset));
+        }
+        at = next;
+    }
+    out.push("END:VTIMEZONE");
+}
+
+fn write_observance<O: OffsetComponents + OffsetName + Offset>(
+    out: &mut Lines,
+    from: &O,
+    to: &O,
+    start: String,
+) {
+    let kind = if to.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    out.push(&format!("BEGIN:{kind}"));
+    out.push(&format!("DTSTART:{start}"));
+    out.push(&format!("TZOFFSETFROM:{}", format_offset(seconds(from))));
+    out.push(&format!("TZOFFSETTO:{}", format_offset(seconds(to))));
+    if let Some(abbreviation) = to.abbreviation() {
+        out.push(&format!("TZNAME:{}", escape(abbreviation)));
+    }
+    out.push(&format!("END:{kind}"));
+}
+
+fn offset_at(tz: Tz, at: DateTime<Utc>) -> i32 {
+    seconds(&tz.offset_from_utc_datetime(&at.naive_utc()))
+}
+
+/// First whole minute in `(lo, hi]` with the offset in effect at `hi`.
+/// Transitions fall on whole minutes.
+fn find_transition(tz: Tz, lo: DateTime<Utc>, hi: DateTime<Utc>) -> DateTime<Utc> {
+    let minute = |m: i64| DateTime::from_timestamp(m * 60, 0).unwrap_or(hi);
+    let target = offset_at(tz, hi);
+    let (mut lo, mut hi) = (lo.timestamp().div_euclid(60), hi.timestamp().div_euclid(60));
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if offset_at(tz, minute(mid)) == target {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    minute(hi)
+}
+
+fn seconds<O: Offset>(offset: &O) -> i32 {
+    offset.fix().local_minus_utc()
+}
+
+fn format_local(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// `-0500`, `+0530`.
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+/// Segment times look like `2026-02-15T06:00-07:00`, sometimes with seconds.
+fn parse_time(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M%:z"))
+        .ok()
+}
+
+fn airport_code(airport: Option<&Airport>) -> &str {
+    airport.and_then(|a| a.code.as_deref()).unwrap_or("?")
+}
+
+fn airport_name(airport: Option<&Airport>) -> String {
+    match airport {
+        Some(Airport {
+            code: Some(code),
+            name: Some(name),
+            ..
+        }) => format!("{name} ({code})"),
+        _ => airport_code(airport).to_string(),
+    }
+}
+
+/// TEXT value escaping (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Content lines, CRLF-terminated and folded at 75 octets.
+#[derive(Default)]
+struct Lines(String);
+
+impl Lines {
+    fn push(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                self.0.push_str("\r\n ");
+                width = 1;
+            }
+            self.0.push(c);
+            width += c.len_utf8();
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details() -> BookingDetails {
+        serde_json::from_value(serde_json::json!({
+            "itinerary": { "slices": [{
+                "segments": [
+                    {
+                        "carrier": { "code": "AA", "name": "American Airlines" },
+                        "flightNumber": "1234",
+                        "origin": { "code": "PHX", "name": "Phoenix Sky Harbor" },
+                        "destination": { "code": "DFW", "name": "Dallas/Fort Worth" },
+                        "departure": "2026-03-07T22:00-07:00",
+                        "arrival": "2026-03-08T02:40-06:00",
+                        "aircraft": { "code": "321", "name": "Airbus A321" },
+                        "bookingInfos": [{ "bookingCode": "O", "cabin": "COACH" }]
+                    },
+                    {
+                        "carrier": { "code": "AA" },
+                        "flightNumber": "567",
+                        "origin": { "code": "DFW" },
+                        "destination": { "code": "XXX" },
+                        "departure": "2026-03-08T09:00-05:00",
+                        "arrival": "2026-03-08T12:00-04:00"
+                    }
+                ]
+            }]}
+        }))
+        .unwrap()
+    }
+
+    fn calendar() -> Calendar {
+        Calendar::new().stamp(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn events_in_local_time() {
+        let ics = calendar().render(&details()).unwrap();
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("DTSTART;TZID=America/Phoenix:20260307T220000\r\n"));
+        assert!(ics.contains("DTEND;TZID=America/Chicago:20260308T034000\r\n"));
+        assert!(ics.contains("DTSTART;TZID=America/Chicago:20260308T090000\r\n"));
+        // XXX is not in the airport table.
+        assert!(ics.contains("DTEND:20260308T160000Z\r\n"));
+        assert!(ics.contains("SUMMARY:AA1234 PHX-DFW\r\n"));
+        assert!(ics.contains("DTSTAMP:20260101T000000Z\r\n"));
+        assert!(
+            ics.contains("Booking class: O (COACH)"),
+            "{}",
+            ics.replace("\r\n ", "")
+        );
+    }
+
+    #[test]
+    fn vtimezone_covers_dst_change() {
+        let ics = calendar().render(&details()).unwrap();
+        assert!(ics.contains("TZID:America/Phoenix\r\nBEGIN:STANDARD\r\n"));
+        let chicago = &ics[ics.find("TZID:America/Chicago").unwrap()..];
+        let chicago = &chicago[..chicago.find("END:VTIMEZONE").unwrap()];
+        assert!(chicago.contains(
+            "BEGIN:DAYLIGHT\r\nDTSTART:20260308T020000\r\n\
+             TZOFFSETFROM:-0600\r\nTZOFFSETTO:-0500\r\nTZNAME:CDT\r\n"
+        ));
+    }
+
+    #[test]
+    fn location_timezone_overrides_table() {
+        let location: Location = serde_json::from_value(serde_json::json!({
+            "code": "XXX", "displayName": "Somewhere", "timezone": "America/New_York"
+        }))
+        .unwrap();
+        let ics = calendar().location(&location).render(&details()).unwrap();
+        assert!(ics.contains("DTEND;TZID=America/New_York:20260308T120000\r\n"));
+    }
+
+    #[test]
+    fn reports_bad_times() {
+        let mut details = details();
+        let segment = &mut details.itinerary.as_mut().unwrap().slices[0].segments[1];
+        segment.departure = Some("tomorrow".to_string());
+        assert!(matches!(
+            to_ics(&details),
+            Err(IcsError::InvalidTime { segment: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn folds_and_escapes() {
+        let mut lines = Lines::default();
+        lines.push(&format!("DESCRIPTION:{}", escape(&"a, b; c\n".repeat(10))));
+        for line in lines.0.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert!(lines.0.starts_with("DESCRIPTION:a\\, b\\; c\\na\\, "));
+    }
+}
@@ -1,7 +1,17 @@
+pub mod cache;
 pub mod client;
+pub mod diff;
 pub mod error;
+pub mod export;
+pub mod ics;
+pub mod links;
 pub mod model;
+pub mod notify;
+pub mod reference;
+pub mod render;
+pub mod saved;
 pub mod transport;
+pub mod watch;
 
 pub use client::ItaClient;
 pub use error::ItaError;
@@ -0,0 +1,632 @@
+//! Links back to the Matrix web UI and, best effort, to airline booking
+//! pages.
+//!
+//! The Matrix UI takes the whole search as base64-encoded JSON in its
+//! `search` parameter. Airline URLs are not documented and change without
+//! notice; treat them as a convenience, not an API.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::NaiveDateTime;
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::model::common::{Cabin, Pax};
+use crate::model::response::{BookingDetails, Segment};
+use crate::model::routing::{RoutingCode, SliceView};
+use crate::model::search::{SearchRequest, Solution};
+
+const MATRIX_URL: &str = "https://matrix.itasoftware.com/flights";
+
+/// Opens the same search in the Matrix web UI.
+pub fn matrix_url(request: &SearchRequest) -> Url {
+    let search =
+        serde_json::to_vec(&MatrixSearch::from(request)).expect("matrix search serializes to JSON");
+    let mut url = Url::parse(MATRIX_URL).expect("valid base URL");
+    url.query_pairs_mut()
+        .append_pair("search", &STANDARD.encode(search));
+    url
+}
+
+/// Opens a search in the Matrix web UI that can only return the flights of
+/// `solution`, keeping the passengers and options of `request`.
+pub fn matrix_solution_url(solution: &Solution, request: &SearchRequest) -> Url {
+    matrix_url(&solution_request(solution, request))
+}
+
+/// `request` with its slices replaced by the solution's, each pinned to its
+/// flights with a routing code. Slices whose flights cannot all be read keep
+/// no routing code.
+pub fn solution_request(solution: &Solution, request: &SearchRequest) -> SearchRequest {
+    let mut request = request.clone();
+    let template = request.inputs.slices.first().cloned();
+    let Some(template) = template else {
+        return request;
+    };
+    request.inputs.slices = solution
+        .slices()
+        .iter()
+        .map(|slice| {
+            let mut out = template.clone();
+            out.origins = slice.origin.iter().filter_map(|a| a.code.clone()).collect();
+            out.destinations = slice
+                .destination
+                .iter()
+                .filter_map(|a| a.code.clone())
+                .collect();
+            out.date = slice
+                .departure
+                .as_deref()
+                .and_then(|d| d.get(..10))
+                .unwrap_or_default()
+                .to_string();
+            out.route_language = pinned_flights(&SliceView::from(slice)).map(|r| r.build());
+            out.command_line = None;
+            out
+        })
+        .collect();
+    request
+}
+
+fn pinned_flights(view: &SliceView) -> Option<RoutingCode> {
+    if view.legs.is_empty() {
+        return None;
+    }
+    view.legs
+        .iter()
+        .try_fold(RoutingCode::default(), |code, leg| {
+            Some(code.flight(leg.carrier.as_deref()?, leg.number?))
+        })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixSearch {
+    #[serde(rename = "type")]
+    trip_type: &'static str,
+    slices: Vec<MatrixSlice>,
+    options: MatrixOptions,
+    pax: MatrixPax,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixSlice {
+    origin: Vec<String>,
+    dest: Vec<String>,
+    routing: String,
+    ext: String,
+    routing_ret: String,
+    ext_ret: String,
+    dates: MatrixDates,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixDates {
+    search_date_type: &'static str,
+    departure_date: String,
+    departure_date_type: &'static str,
+    departure_date_modifier: String,
+    departure_date_preferred_times: Vec<u32>,
+    return_date: String,
+    return_date_type: &'static str,
+    return_date_modifier: String,
+    return_date_preferred_times: Vec<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixOptions {
+    cabin: Cabin,
+    stops: String,
+    extra_stops: String,
+    allow_airport_changes: String,
+    show_only_available: String,
+}
+
+/// The UI wants every count as a string.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixPax {
+    adults: String,
+    seniors: String,
+    youth: String,
+    children: String,
+    infants_in_lap: String,
+    infants_in_seat: String,
+}
+
+impl From<&SearchRequest> for MatrixSearch {
+    fn from(request: &SearchRequest) -> Self {
+        let inputs = &request.inputs;
+        let slices = &inputs.slices;
+        let round_trip = matches!(slices.as_slice(), [out, back]
+            if back.origins == out.destinations && back.destinations == out.origins);
+
+        let slice = |s: &crate::model::search::Slice| MatrixSlice {
+            origin: s.origins.clone(),
+            dest: s.destinations.clone(),
+            routing: s.route_language.clone().unwrap_or_default(),
+            ext: s.command_line.clone().unwrap_or_default(),
+            routing_ret: String::new(),
+            ext_ret: String::new(),
+            dates: MatrixDates {
+                search_date_type: "specific",
+                departure_date: s.date.clone(),
+                departure_date_type: if s.is_arrival_date {
+                    "arrive"
+                } else {
+                    "depart"
+                },
+                departure_date_modifier: s
+                    .date_modifier
+                    .minus
+                    .max(s.date_modifier.plus)
+                    .to_string(),
+                departure_date_preferred_times: Vec::new(),
+                return_date: String::new(),
+                return_date_type: "depart",
+                return_date_modifier: "0".to_string(),
+                return_date_preferred_times: Vec::new(),
+            },
+        };
+
+        let (trip_type, slices) = if round_trip {
+            let (out, back) = (&slices[0], &slices[1]);
+            let mut merged = slice(out);
+            merged.routing_ret = back.route_language.clone().unwrap_or_default();
+            merged.ext_ret = back.command_line.clone().unwrap_or_default();
+            merged.dates.return_date = back.date.clone();
+            merged.dates.return_date_type = if back.is_arrival_date {
+                "arrive"
+            } else {
+                "depart"
+            };
+            merged.dates.return_date_modifier = back
+                .date_modifier
+                .minus
+                .max(back.date_modifier.plus)
+                .to_string();
+            ("round-trip", vec![merged])
+        } else if slices.len() == 1 {
+            ("one-way", slices.iter().map(slice).collect())
+        } else {
+            ("multi-city", slices.iter().map(slice).collect())
+        };
+
+        let pax = &inputs.pax;
+        Self {
+            trip_type,
+            slices,
+            options: MatrixOptions {
+                cabin: inputs.cabin,
+                stops: inputs
+                    .filter
+                    .max_stop_count
+                    .map_or_else(|| "-1".to_string(), |n| n.to_string()),
+                extra_stops: inputs.max_legs_relative_to_min.to_string(),
+                allow_airport_changes: inputs.change_of_airport.to_string(),
+                show_only_available: inputs.check_availability.to_string(),
+            },
+            pax: MatrixPax {
+                adults: pax.adults.to_string(),
+                seniors: pax.seniors.to_string(),
+                youth: pax.youth.to_string(),
+                children: pax.children.to_string(),
+                infants_in_lap: pax.infants_in_lap.to_string(),
+                infants_in_seat: pax.infants_in_seat.to_string(),
+            },
+        }
+    }
+}
+
+/// A link to book or re-search an itinerary on the airline's site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookingLink {
+    pub carrier: String,
+    pub url: Url,
+    /// The link selects these exact flights and booking classes; otherwise
+    /// it only prefills the route, dates and passengers.
+    pub exact: bool,
+}
+
+/// Carriers [`booking_link`] knows how to link to.
+pub const BOOKING_CARRIERS: &[&str] = &["AA", "DL", "UA"];
+
+/// A booking link when every flight is marketed by one supported carrier.
+///
+/// Delta links select the exact flights and booking classes, which needs
+/// the segment times and booking codes of [`BookingDetails`]; from a
+/// solution list, where those are missing, there is no Delta link. American
+/// and United links open a search for the same route and dates.
+pub fn booking_link(solution: &Solution, pax: &Pax) -> Option<BookingLink> {
+    let trip: Vec<TripSlice> = solution
+        .slices()
+        .iter()
+        .map(|slice| {
+            let date = slice
+                .departure
+                .as_deref()
+                .and_then(|d| d.get(..10))
+                .map(str::to_string);
+            if slice.segments.is_empty() {
+                TripSlice::from_view(&SliceView::from(slice), date)
+            } else {
+                TripSlice::from_segments(&slice.segments)
+            }
+        })
+        .collect();
+    link(&trip, pax)
+}
+
+pub fn booking_link_for_details(details: &BookingDetails, pax: &Pax) -> Option<BookingLink> {
+    let trip: Vec<TripSlice> = details
+        .itinerary
+        .iter()
+        .flat_map(|it| &it.slices)
+        .map(|slice| TripSlice::from_segments(&slice.segments))
+        .collect();
+    link(&trip, pax)
+}
+
+#[derive(Debug)]
+struct TripSlice {
+    legs: Vec<TripLeg>,
+    date: Option<String>,
+}
+
+#[derive(Debug)]
+struct TripLeg {
+    carrier: Option<String>,
+    number: Option<u32>,
+    origin: Option<String>,
+    destination: Option<String>,
+    booking_code: Option<String>,
+    /// Local departure time.
+    departure: Option<NaiveDateTime>,
+}
+
+impl TripSlice {
+    fn from_view(view: &SliceView, date: Option<String>) -> Self {
+        let legs = view
+            .legs
+            .iter()
+            .map(|leg| TripLeg {
+                carrier: leg.carrier.clone(),
+                number: leg.number,
+                origin: leg.origin.clone(),
+                destination: leg.destination.clone(),
+                booking_code: None,
+                departure: None,
+            })
+            .collect();
+        Self { legs, date }
+    }
+
+    fn from_segments(segments: &[Segment]) -> Self {
+        let legs: Vec<TripLeg> = segments
+            .iter()
+            .map(|s| TripLeg {
+                carrier: s.carrier.as_ref().and_then(|c| c.code.clone()),
+                number: s
+                    .flight_number
+                    .as_deref()
+                    .and_then(|n| n.trim().parse().ok()),
+                origin: s.origin.as_ref().and_then(|a| a.code.clone()),
+                destination: s.destination.as_ref().and_then(|a| a.code.clone()),
+                booking_code: s.booking_infos.first().and_then(|b| b.booking_code.clone()),
+                departure: s.departure.as_deref().and_then(|d| {
+                    NaiveDateTime::parse_from_str(d.get(..16)?, "%Y-%m-%dT%H:%M").ok()
+                }),
+            })
+            .collect();
+        let date = legs
+            .first()
+            .and_then(|l| l.departure)
+            .map(|d| d.date().to_string());
+        Self { legs, date }
+    }
+
+    fn origin(&self) -> Option<&str> {
+        self.legs.first()?.origin.as_deref()
+    }
+
+    fn destination(&self) -> Option<&str> {
+        self.legs.last()?.destination.as_deref()
+    }
+}
+
+fn link(trip: &[TripSlice], pax: &Pax) -> Option<BookingLink> {
+    let mut carriers = trip
+        .iter()
+        .flat_map(|s| &s.legs)
+        .map(|l| l.carrier.as_deref());
+    let carrier = carriers.next()??;
+    if !carriers.all(|c| c == Some(carrier)) || trip.iter().any(|s| s.legs.is_empty()) {
+        return None;
+    }
+    // Lap infants don't take a seat. Summed wide so large counts can't overflow.
+    let passengers: u16 = [
+        pax.adults,
+        pax.children,
+        pax.seniors,
+        pax.youth,
+        pax.infants_in_seat,
+    ]
+    .into_iter()
+    .map(u16::from)
+    .sum();
+    let (url, exact) = match carrier {
+        "AA" => (american(trip, passengers)?, false),
+        "DL" => (delta(trip, passengers)?, true),
+        "UA" => (united(trip, passengers)?, false),
+        _ => return None,
+    };
+    Some(BookingLink {
+        carrier: carrier.to_string(),
+        url,
+        exact,
+    })
+}
+
+fn is_round_trip(trip: &[TripSlice]) -> bool {
+    matches!(trip, [out, back]
+        if out.origin() == back.destination() && out.destination() == back.origin())
+}
+
+fn american(trip: &[TripSlice], passengers: u16) -> Option<Url> {
+    #[derive(Serialize)]
+    struct AaSlice<'a> {
+        orig: &'a str,
+        dest: &'a str,
+        date: &'a str,
+    }
+    let slices = trip
+        .iter()
+        .map(|s| {
+            Some(AaSlice {
+                orig: s.origin()?,
+                dest: s.destination()?,
+                date: s.date.as_deref()?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let trip_type = match trip.len() {
+        1 => "OneWay",
+        _ if is_round_trip(trip) => "RoundTrip",
+        _ => "MultiCity",
+    };
+    let mut url = Url::parse("https://www.aa.com/booking/search").ok()?;
+    url.query_pairs_mut()
+        .append_pair("locale", "en_US")
+        .append_pair("pax", &passengers.to_string())
+        .append_pair("adult", &passengers.to_string())
+        .append_pair("type", trip_type)
+        .append_pair("searchType", "Revenue")
+        .append_pair("carriers", "ALL")
+        .append_pair("slices", &serde_json::to_string(&slices).ok()?);
+    Some(url)
+}
+
+fn united(trip: &[TripSlice], passengers: u16) -> Option<Url> {
+    let out = trip.first()?;
+    let mut url = Url::parse("https://www.united.com/en/us/fsr/choose-flights").ok()?;
+    url.query_pairs_mut()
+        .append_pair("f", out.origin()?)
+        .append_pair("t", out.destination()?)
+        .append_pair("d", out.date.as_deref()?);
+    match trip.len() {
+        1 => {
+            url.query_pairs_mut().append_pair("tt", "1");
+        }
+        2 if is_round_trip(trip) => {
+            url.query_pairs_mut()
+                .append_pair("r", trip[1].date.as_deref()?)
+                .append_pair("tt", "0");
+        }
+        // The search form takes one-way and round trips only.
+        _ => return None,
+    }
+    url.query_pairs_mut()
+        .append_pair("px", &passengers.to_string())
+        .append_pair("taxng", "1");
+    Some(url)
+}
+
+/// One `itinSegment[i]` per flight:
+/// `slice:class:origin:destination:carrier:number:Mon:DD:HH:MMa`.
+fn delta(trip: &[TripSlice], passengers: u16) -> Option<Url> {
+    let trip_type = match trip.len() {
+        1 => "ONE_WAY",
+        _ if is_round_trip(trip) => "ROUND_TRIP",
+        _ => "MULTI_CITY",
+    };
+    let mut segments = Vec::new();
+    for (i, slice) in trip.iter().enumerate() {
+        for leg in &slice.legs {
+            let departure = leg.departure?;
+            segments.push(format!(
+                "{i}:{}:{}:{}:{}:{}:{}:{}",
+                leg.booking_code.as_deref()?,
+                leg.origin.as_deref()?,
+                leg.destination.as_deref()?,
+                leg.carrier.as_deref()?,
+                leg.number?,
+                departure.format("%b:%d"),
+                departure
+                    .format("%I:%M%P")
+                    .to_string()
+                    .trim_end_matches('m'),
+            ));
+        }
+    }
+
+    let mut url = Url::parse("https://www.delta.com/air-shopping/priceTripAction.action").ok()?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("ftw_reroute", "true")
+            .append_pair("tripType", trip_type)
+            .append_pair("paxCount", &passengers.to_string())
+            .append_pair("numOfSegments", &segments.len().to_string());
+        for (i, segment) in segments.iter().enumerate() {
+            query.append_pair(&format!("itinSegment[{i}]"), segment);
+        }
+    }
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(url: &Url) -> serde_json::Value {
+        let (_, search) = url.query_pairs().find(|(k, _)| k == "search").unwrap();
+        serde_json::from_slice(&STANDARD.decode(search.as_bytes()).unwrap()).unwrap()
+    }
+
+    fn solution() -> Solution {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc123",
+            "itinerary": { "slices": [{
+                "origin": { "code": "PHX" },
+                "destination": { "code": "DTW" },
+                "departure": "2026-02-15T06:00-07:00",
+                "flights": ["DL1234", "DL567"],
+                "stops": [{ "code": "MSP" }]
+            }]}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let mut request = SearchRequest::builder()
+            .round_trip(&["BOS"], &["LAX", "BUR"], "2026-06-01", "2026-06-08")
+            .adults(2)
+            .build();
+        request.inputs.slices[1].route_language = Some("C:UA+".to_string());
+        let url = matrix_url(&request);
+        assert!(
+            url.as_str()
+                .starts_with("https://matrix.itasoftware.com/flights?search=")
+        );
+
+        let search = decode(&url);
+        assert_eq!(search["type"], "round-trip");
+        let slice = &search["slices"][0];
+        assert_eq!(slice["origin"], serde_json::json!(["BOS"]));
+        assert_eq!(slice["dest"], serde_json::json!(["LAX", "BUR"]));
+        assert_eq!(slice["routingRet"], "C:UA+");
+        assert_eq!(slice["dates"]["departureDate"], "2026-06-01");
+        assert_eq!(slice["dates"]["returnDate"], "2026-06-08");
+        assert_eq!(search["options"]["cabin"], "COACH");
+        assert_eq!(search["pax"]["adults"], "2");
+    }
+
+    #[test]
+    fn matrix_multi_city() {
+        let request = SearchRequest::builder()
+            .add_slice(&["BOS"], &["LHR"], "2026-06-01")
+            .add_slice(&["CDG"], &["BOS"], "2026-06-10")
+            .build();
+        let search = decode(&matrix_url(&request));
+        assert_eq!(search["type"], "multi-city");
+        assert_eq!(search["slices"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn solution_pins_flights() {
+        let request = SearchRequest::builder()
+            .add_slice(&["PHX"], &["DTT"], "2026-02-14")
+            .build();
+        let pinned = solution_request(&solution(), &request);
+        let slice = &pinned.inputs.slices[0];
+        assert_eq!(slice.origins, ["PHX"]);
+        assert_eq!(slice.destinations, ["DTW"]);
+        assert_eq!(slice.date, "2026-02-15");
+        assert_eq!(slice.route_language.as_deref(), Some("F:DL1234 F:DL567"));
+
+        let search = decode(&matrix_solution_url(&solution(), &request));
+        assert_eq!(search["slices"][0]["routing"], "F:DL1234 F:DL567");
+    }
+
+    #[test]
+    fn united_search_link() {
+        let mut solution = solution();
+        solution.itinerary.as_mut().unwrap().slices[0].flights =
+            vec!["UA1".to_string(), "UA2".to_string()];
+        let link = booking_link(&solution, &Pax::default()).unwrap();
+        assert_eq!(link.carrier, "UA");
+        assert!(!link.exact);
+        assert_eq!(
+            link.url.as_str(),
+            "https://www.united.com/en/us/fsr/choose-flights?f=PHX&t=DTW&d=2026-02-15&tt=1&px=1&taxng=1"
+        );
+    }
+
+    #[test]
+    fn large_party_counts_every_seat() {
+        let mut solution = solution();
+        solution.itinerary.as_mut().unwrap().slices[0].flights =
+            vec!["UA1".to_string(), "UA2".to_string()];
+        let pax = Pax {
+            adults: 200,
+            children: 100,
+            infants_in_lap: 9,
+            ..Pax::default()
+        };
+        let link = booking_link(&solution, &pax).unwrap();
+        let (_, px) = link.url.query_pairs().find(|(k, _)| k == "px").unwrap();
+        assert_eq!(px, "300");
+    }
+
+    #[test]
+    fn delta_needs_segments() {
+        assert_eq!(booking_link(&solution(), &Pax::default()), None);
+
+        let details: BookingDetails = serde_json::from_value(serde_json::json!({
+            "itinerary": { "slices": [{ "segments": [
+                {
+                    "carrier": { "code": "DL" }, "flightNumber": "1234",
+                    "origin": { "code": "PHX" }, "destination": { "code": "MSP" },
+                    "departure": "2026-02-15T06:00-07:00",
+                    "bookingInfos": [{ "bookingCode": "V" }]
+                },
+                {
+                    "carrier": { "code": "DL" }, "flightNumber": "567",
+                    "origin": { "code": "MSP" }, "destination": { "code": "DTW" },
+                    "departure": "2026-02-15T13:05-06:00",
+                    "bookingInfos": [{ "bookingCode": "V" }]
+                }
+            ]}]}
+        }))
+        .unwrap();
+        let link = booking_link_for_details(&details, &Pax::default()).unwrap();
+        assert!(link.exact);
+        let segments: Vec<String> = link
+            .url
+            .query_pairs()
+            .filter(|(k, _)| k.starts_with("itinSegment"))
+            .map(|(_, v)| v.into_owned())
+            .collect();
+        assert_eq!(
+            segments,
+            [
+                "0:V:PHX:MSP:DL:1234:Feb:15:06:00a",
+                "0:V:MSP:DTW:DL:567:Feb:15:01:05p"
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_or_unknown_carriers_have_no_link() {
+        for flights in [vec!["DL1", "AF2"], vec!["AF1"]] {
+            let mut solution = solution();
+            solution.itinerary.as_mut().unwrap().slices[0].flights =
+                flights.into_iter().map(String::from).collect();
+            assert_eq!(booking_link(&solution, &Pax::default()), None);
+        }
+    }
+}
@@ -1,55 +1,591 @@
-use ita_matrix::ItaClient;
-use ita_matrix::model::search::SearchRequest;
-use ita_matrix::model::summarize::SummarizeRequest;
-use tracing::info;
+mod cli;
+
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Local, Utc};
+
+use clap::{Parser, ValueEnum};
+use ita_matrix::cache::{Cache, CacheConfig, CacheMode};
+use ita_matrix::diff;
+use ita_matrix::export::{self, ExportFormat};
+use ita_matrix::ics::Calendar;
+use ita_matrix::links;
+use ita_matrix::model::money::Money;
+use ita_matrix::model::response::BookingDetails;
+use ita_matrix::model::search::{ApiErrorBody, SearchRequest, SearchResponse, Solution};
+use ita_matrix::model::summarize::{SummarizeRequest, SummarizeResponse};
+use ita_matrix::notify::{Alert, Delivery, Notifier, NotifyConfig};
+use ita_matrix::reference::AirportDb;
+use ita_matrix::render::{self, RenderOptions};
+use ita_matrix::saved::SavedSearches;
+use ita_matrix::watch::{AlertRules, Observation, PriceChange, Watch, WatchError, WatchStore};
+use ita_matrix::{ItaClient, ItaError};
+use serde::Serialize;
+
+use cli::{CalendarArgs, Cli, Command, Format, SearchArgs, WatchCommand};
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     tracing_subscriber::fmt()
         .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
         )
+        .with_writer(std::io::stderr)
         .init();
 
-    let client = ItaClient::new()?;
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+/// Exit status for scripts: one code per [`ItaError`] variant, 1 for
+/// anything else. Usage errors exit with 2 from clap.
+fn exit_code(error: &anyhow::Error) -> u8 {
+    match error.downcast_ref::<ItaError>() {
+        Some(ItaError::Api { .. }) => 10,
+        Some(ItaError::Transport(_)) => 11,
+        Some(ItaError::Json(_)) => 12,
+        Some(ItaError::UnexpectedStatus { .. }) => 13,
+        Some(ItaError::BatchParse { .. }) => 14,
+        Some(ItaError::MissingField { .. }) => 15,
+        Some(ItaError::EmptyArgument(_)) => 16,
+        None => 1,
+    }
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    let mut client = match &cli.api_key {
+        Some(key) => ItaClient::with_api_key(key)?,
+        None => ItaClient::new()?,
+    };
+    if let Some(dir) = &cli.cache_dir {
+        let mode = if cli.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Normal
+        };
+        client = client.with_cache(Cache::new(CacheConfig::new().dir(dir).mode(mode)));
+    }
+    let output = Output::new(&cli);
+
+    match cli.command {
+        Command::Search(args) => {
+            let show_link = args.matrix_link;
+            let request = search_request(args);
+            if show_link {
+                eprintln!("{}", links::matrix_url(&request));
+            }
+            let response = client.search(&request).await?;
+            check_api_error(response.error.as_ref())?;
+            output.search(&response)
+        }
+        Command::Run { file, name, today } => {
+            let today = today.unwrap_or_else(|| Local::now().date_naive());
+            let saved = SavedSearches::load(&file)?;
+            let mut requests = Vec::new();
+            for search in &saved.searches {
+                if name.is_empty() || name.contains(&search.name) {
+                    requests.push((search.name.as_str(), search.to_request(today)?));
+                }
+            }
+            if requests.is_empty() {
+                anyhow::bail!("no matching searches in {}", file.display());
+            }
+            let mut responses = Vec::new();
+            for (name, request) in requests {
+                let response = client.search(&request).await?;
+                check_api_error(response.error.as_ref())?;
+                responses.push((name, response));
+            }
+            output.saved_searches(&responses)
+        }
+        Command::Calendar(args) => {
+            let response = client.search(&calendar_request(args)).await?;
+            check_api_error(response.error.as_ref())?;
+            output.json(&response)
+        }
+        Command::Details(s) => {
+            let request =
+                SummarizeRequest::booking_details(&s.solution_set, &s.session, &s.solution);
+            let response = client.summarize(&request).await?;
+            check_api_error(response.error.as_ref())?;
+            match (&response.booking_details, output.format) {
+                (Some(details), Format::Ics) => {
+                    print!("{}", calendar(&client, details).await.render(details)?);
+                    Ok(())
+                }
+                (Some(details), Format::Text) => {
+                    print!("{}", render::plain_itinerary(details));
+                    Ok(())
+                }
+                _ => output.details(&s.solution, &response),
+            }
+        }
+        Command::Rules {
+            solution: s,
+            pricing,
+            slice,
+        } => {
+            let request = SummarizeRequest::fare_rules(
+                &s.solution_set,
+                &s.session,
+                &s.solution,
+                pricing,
+                slice,
+            );
+            let response = client.summarize(&request).await?;
+            check_api_error(response.error.as_ref())?;
+            output.json(&response)
+        }
+        Command::Diff { before, after } => {
+            let diff = diff::diff_solutions(&load_solutions(&before)?, &load_solutions(&after)?);
+            match output.format {
+                Format::Table => {
+                    print!("{}", render::result_diff(&diff, &output.options));
+                    Ok(())
+                }
+                _ => output.json(&diff),
+            }
+        }
+        Command::Watch { db, command } => watch(&client, &output, db, command).await,
+        Command::Autocomplete { query, limit } => {
+            output.json(&client.autocomplete(&query, limit).await?)
+        }
+        Command::Airport { code } => output.json(&client.lookup_airport(&code).await?),
+        Command::Currencies => output.json(&client.currencies().await?),
+    }
+}
 
-    info!("--- autocomplete ---");
-    let locations = client.autocomplete("PHX", 10).await?;
-    println!("{}", serde_json::to_string_pretty(&locations)?);
+async fn watch(
+    client: &ItaClient,
+    output: &Output,
+    db: Option<PathBuf>,
+    command: WatchCommand,
+) -> anyhow::Result<()> {
+    let path = match db {
+        Some(path) => path,
+        None => WatchStore::default_path().context("no user data directory; pass --db")?,
+    };
+    let store = WatchStore::open(&path)?;
 
-    info!("--- currencies ---");
-    let currencies = client.currencies().await?;
-    println!("{} currencies loaded", currencies.len());
+    match command {
+        WatchCommand::Add {
+            file,
+            name,
+            every,
+            top,
+            below,
+            new_low,
+            drop,
+        } => {
+            let alerts = AlertRules {
+                below,
+                new_low,
+                drop_percent: drop,
+            };
+            let today = Local::now().date_naive();
+            let mut added = 0;
+            for search in SavedSearches::load(&file)?.searches {
+                if !name.is_empty() && !name.contains(&search.name) {
+                    continue;
+                }
+                // Reject definitions that could never run.
+                search.to_request(today)?;
+                let watch = Watch::new(search)
+                    .interval(every)
+                    .top_n(top)
+                    .alerts(alerts.clone());
+                store.add(&watch)?;
+                eprintln!("watching {}", watch.name);
+                added += 1;
+            }
+            if added == 0 {
+                anyhow::bail!("no matching searches in {}", file.display());
+            }
+            Ok(())
+        }
+        WatchCommand::List => output.watches(&store, &store.watches()?),
+        WatchCommand::Remove { name } => Ok(store.remove(&name)?),
+        WatchCommand::Run {
+            mut all,
+            repeat,
+            notify,
+        } => {
+            let notifier = match notify {
+                Some(path) => Some(Notifier::new(NotifyConfig::load(&path)?)?),
+                None => None,
+            };
+            loop {
+                let due = if all {
+                    store.watches()?
+                } else {
+                    store.due(Utc::now())?
+                };
+                let mut ran = Vec::new();
+                let mut failed = 0;
+                for watch in due {
+                    match run_watch(client, &store, &watch).await {
+                        Ok(()) => {
+                            send_alert(notifier.as_ref(), &store, &watch).await?;
+                            ran.push(watch);
+                        }
+                        Err(e) => {
+                            tracing::warn!(watch = %watch.name, error = %format!("{e:#}"), "watch failed");
+                            failed += 1;
+                        }
+                    }
+                }
+                if !ran.is_empty() {
+                    output.watches(&store, &ran)?;
+                }
+                if !repeat {
+                    if failed > 0 {
+                        anyhow::bail!("{failed} watch(es) failed");
+                    }
+                    return Ok(());
+                }
 
-    info!("--- airport lookup ---");
-    let airport = client.lookup_airport("PHX").await?;
-    println!("{}", serde_json::to_string_pretty(&airport)?);
+                all = false;
+                let mut next: Option<DateTime<Utc>> = None;
+                for watch in store.watches()? {
+                    let at = watch.next_run(store.last_run(&watch.name)?);
+                    next = Some(next.map_or(at, |next| next.min(at)));
+                }
+                let Some(next) = next else {
+                    anyhow::bail!("no watches to run");
+                };
+                tracing::info!(%next, "waiting for the next watch");
+                let wait = (next - Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait.max(Duration::from_secs(60))).await;
+            }
+        }
+        WatchCommand::History { name, limit } => {
+            if store.get(&name)?.is_none() {
+                return Err(WatchError::NotFound(name).into());
+            }
+            let mut history = store.history(&name)?;
+            if let Some(limit) = limit {
+                history.drain(..history.len().saturating_sub(limit));
+            }
+            match output.format {
+                Format::Table => {
+                    print!("{}", render::price_history(&history, &output.options));
+                    Ok(())
+                }
+                _ => output.json(&history),
+            }
+        }
+    }
+}
 
-    info!("--- flight search ---");
-    let search_req = SearchRequest::builder()
-        .origins(&["PHX"])
-        .destinations(&["DTW"])
-        .date("2026-02-15")
-        .build();
+/// The solutions in a saved search response, or a bare solution list.
+fn load_solutions(path: &Path) -> anyhow::Result<Vec<Solution>> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&data)
+        .with_context(|| format!("invalid JSON in {}", path.display()))?;
+    if value.is_array() {
+        return Ok(serde_json::from_value(value)?);
+    }
+    let response: SearchResponse = serde_json::from_value(value)?;
+    Ok(response
+        .solution_list
+        .map(|list| list.solutions)
+        .unwrap_or_default())
+}
 
-    let search_resp = client.search(&search_req).await?;
-    println!("{}", serde_json::to_string_pretty(&search_resp)?);
+/// Report the alert for the watch's latest run, if any, and send it when
+/// notifications are configured. Delivery failures are only logged.
+async fn send_alert(
+    notifier: Option<&Notifier>,
+    store: &WatchStore,
+    watch: &Watch,
+) -> anyhow::Result<()> {
+    let Some(alert) = Alert::check(watch, &store.history(&watch.name)?) else {
+        return Ok(());
+    };
+    eprintln!("{}", alert.subject());
+    let Some(notifier) = notifier else {
+        return Ok(());
+    };
+    for (sink, delivery) in notifier.notify(store, &alert).await {
+        if let Delivery::Failed(e) = delivery {
+            tracing::warn!(sink, error = %e, "notification failed");
+        }
+    }
+    Ok(())
+}
 
-    if let (Some(ss), Some(sess)) = (&search_resp.solution_set, &search_resp.session) {
-        info!("--- summarize ---");
-        let summarize_req = SummarizeRequest::new(ss, sess);
-        let summarize_resp = client.summarize(&summarize_req).await?;
-        println!("{}", serde_json::to_string_pretty(&summarize_resp)?);
+/// Run one watch's search now and record the result.
+async fn run_watch(client: &ItaClient, store: &WatchStore, watch: &Watch) -> anyhow::Result<()> {
+    let request = watch.search.to_request(Local::now().date_naive())?;
+    let response = client.search(&request).await?;
+    check_api_error(response.error.as_ref())?;
+    let observation = Observation::from_response(&response, Utc::now(), watch.top_n);
+    store.record(&watch.name, &observation)?;
+    Ok(())
+}
 
-        if let Some(ref sol_list) = search_resp.solution_list
-            && let Some(first) = sol_list.solutions.first()
-        {
-            info!("--- booking details ---");
-            let details_req = SummarizeRequest::booking_details(ss, sess, &first.id);
-            let details_resp = client.summarize(&details_req).await?;
-            println!("{}", serde_json::to_string_pretty(&details_resp)?);
+/// A calendar using the embedded airport table, plus a lookup for each
+/// airport it does not know.
+async fn calendar(client: &ItaClient, details: &BookingDetails) -> Calendar {
+    let mut calendar = Calendar::new();
+    let mut codes: Vec<&str> = details
+        .itinerary
+        .iter()
+        .flat_map(|it| &it.slices)
+        .flat_map(|s| &s.segments)
+        .flat_map(|s| [&s.origin, &s.destination])
+        .filter_map(|a| a.as_ref()?.code.as_deref())
+        .filter(|code| AirportDb::embedded().get(code).is_none())
+        .collect();
+    codes.sort_unstable();
+    codes.dedup();
+    for code in codes {
+        match client.lookup_airport(code).await {
+            Ok(location) => calendar = calendar.location(&location),
+            Err(e) => tracing::warn!(code, error = %e, "no time zone for airport"),
         }
     }
+    calendar
+}
+
+fn search_request(args: SearchArgs) -> SearchRequest {
+    let mut builder = SearchRequest::builder()
+        .cabin(args.cabin.into())
+        .pax(args.pax.into())
+        .page_size(args.page_size);
+    if args.slices.is_empty() {
+        let from: Vec<&str> = args.from.iter().map(String::as_str).collect();
+        let to: Vec<&str> = args.to.iter().map(String::as_str).collect();
+        let date = args.date.as_deref().unwrap_or_default();
+        builder = match &args.return_date {
+            Some(back) => builder.round_trip(&from, &to, date, back),
+            None => builder.add_slice(&from, &to, date),
+        };
+    }
+    for slice in &args.slices {
+        let origins: Vec<&str> = slice.origins.iter().map(String::as_str).collect();
+        let destinations: Vec<&str> = slice.destinations.iter().map(String::as_str).collect();
+        builder = builder.add_slice(&origins, &destinations, &slice.date);
+    }
+    if let Some(n) = args.max_stops {
+        builder = builder.max_stops(n);
+    }
+    if let Some(sort) = &args.sort {
+        builder = builder.sort(sort);
+    }
+    if let Some(code) = args.routing {
+        builder = builder.routing(code);
+    }
+    if let Some(code) = args.extension {
+        builder = builder.extension(code);
+    }
+    builder.build()
+}
+
+fn calendar_request(args: CalendarArgs) -> SearchRequest {
+    let from: Vec<&str> = args.from.iter().map(String::as_str).collect();
+    let to: Vec<&str> = args.to.iter().map(String::as_str).collect();
+    match (&args.return_start, &args.return_end) {
+        (Some(start), Some(end)) => {
+            SearchRequest::calendar_round_trip(&from, &to, &args.start, &args.end, start, end)
+        }
+        _ => SearchRequest::calendar(&from, &to, &args.start, &args.end),
+    }
+}
+
+/// Surface an error body in a 200 response as [`ItaError::Api`].
+fn check_api_error(error: Option<&ApiErrorBody>) -> Result<(), ItaError> {
+    match error {
+        Some(err) => Err(ItaError::Api {
+            message: err.message.clone(),
+            error_type: err.error_type.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Where results go: the chosen format, or a table on a terminal and JSON
+/// otherwise.
+struct Output {
+    format: Format,
+    options: RenderOptions,
+}
+
+impl Output {
+    fn new(cli: &Cli) -> Self {
+        let terminal = std::io::stdout().is_terminal();
+        let format = cli.format.unwrap_or(if terminal {
+            Format::Table
+        } else {
+            Format::Json
+        });
+        let options = RenderOptions {
+            width: cli.width.or_else(|| {
+                terminal
+                    .then(|| std::env::var("COLUMNS").ok()?.parse().ok())
+                    .flatten()
+            }),
+            color: !cli.no_color
+                && terminal
+                && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        };
+        Self { format, options }
+    }
+
+    fn export_format(&self) -> Option<ExportFormat> {
+        match self.format {
+            Format::Table | Format::Json | Format::Ics | Format::Text => None,
+            Format::Csv => Some(ExportFormat::Csv),
+            Format::Jsonl => Some(ExportFormat::Jsonl),
+            Format::Segments => Some(ExportFormat::Segments),
+        }
+    }
+
+    fn search(&self, response: &SearchResponse) -> anyhow::Result<()> {
+        let solutions = response
+            .solution_list
+            .as_ref()
+            .map(|list| list.solutions.as_slice())
+            .unwrap_or_default();
+        if matches!(self.format, Format::Ics | Format::Text) {
+            return self.unsupported();
+        }
+        if let Some(format) = self.export_format() {
+            export::export_solutions(solutions, format, std::io::stdout().lock())?;
+            return Ok(());
+        }
+        if self.format == Format::Json || response.solution_list.is_none() {
+            return print_json(response);
+        }
+        if let Some(matrix) = &response.carrier_stop_matrix {
+            println!("{}", render::carrier_stop_matrix(matrix, &self.options));
+        }
+        print!("{}", render::solutions(solutions, &self.options));
+        Ok(())
+    }
+
+    /// Watches with their latest run, as a table or JSON.
+    fn watches(&self, store: &WatchStore, watches: &[Watch]) -> anyhow::Result<()> {
+        let mut histories = Vec::new();
+        for watch in watches {
+            histories.push(store.history(&watch.name)?);
+        }
+        if self.format == Format::Table {
+            let rows: Vec<(&Watch, &[Observation])> = watches
+                .iter()
+                .zip(&histories)
+                .map(|(w, h)| (w, h.as_slice()))
+                .collect();
+            print!("{}", render::watches(&rows, &self.options));
+            return Ok(());
+        }
+
+        #[derive(Serialize)]
+        struct Status<'a> {
+            #[serde(flatten)]
+            watch: &'a Watch,
+            last_run: Option<DateTime<Utc>>,
+            cheapest: Option<Money>,
+            change: Option<PriceChange>,
+        }
+        let statuses: Vec<Status> = watches
+            .iter()
+            .zip(&histories)
+            .map(|(watch, history)| Status {
+                watch,
+                last_run: history.last().map(|o| o.at),
+                cheapest: history.last().and_then(|o| o.cheapest),
+                change: PriceChange::from_history(history),
+            })
+            .collect();
+        self.json(&statuses)
+    }
+
+    /// Results of several named searches: a table each, one JSON object
+    /// keyed by name, or a single export of all their solutions.
+    fn saved_searches(&self, responses: &[(&str, SearchResponse)]) -> anyhow::Result<()> {
+        if let Some(format) = self.export_format() {
+            let solutions: Vec<_> = responses
+                .iter()
+                .filter_map(|(_, r)| r.solution_list.as_ref())
+                .flat_map(|list| list.solutions.iter().cloned())
+                .collect();
+            export::export_solutions(&solutions, format, std::io::stdout().lock())?;
+            return Ok(());
+        }
+        match self.format {
+            Format::Json => {
+                let by_name: BTreeMap<&str, &SearchResponse> =
+                    responses.iter().map(|(name, r)| (*name, r)).collect();
+                print_json(&by_name)
+            }
+            Format::Table => {
+                for (i, (name, response)) in responses.iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    println!("{name}");
+                    self.search(response)?;
+                }
+                Ok(())
+            }
+            _ => self.unsupported(),
+        }
+    }
+
+    fn details(&self, solution_id: &str, response: &SummarizeResponse) -> anyhow::Result<()> {
+        let Some(details) = &response.booking_details else {
+            return self.json(response);
+        };
+        match (self.format, self.export_format()) {
+            (_, Some(format)) => {
+                export::export_booking_details(
+                    solution_id,
+                    details,
+                    format,
+                    std::io::stdout().lock(),
+                )?;
+                Ok(())
+            }
+            (Format::Table, None) => {
+                print!("{}", render::booking_details(details, &self.options));
+                Ok(())
+            }
+            _ => print_json(response),
+        }
+    }
+
+    /// Output with no table or export form; tables fall back to JSON.
+    fn json<T: Serialize>(&self, value: &T) -> anyhow::Result<()> {
+        if !matches!(self.format, Format::Table | Format::Json) {
+            return self.unsupported();
+        }
+        print_json(value)
+    }
+
+    fn unsupported(&self) -> anyhow::Result<()> {
+        let name = self
+            .format
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default();
+        anyhow::bail!("--format {name} is not supported by this command")
+    }
+}
 
+fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
     Ok(())
 }
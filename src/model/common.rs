@@ -33,7 +33,9 @@ pub enum SearchName {
     CalendarFollowup,
 }
 
+/// Deserializes with omitted counts as in `Pax::default()`: one adult.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Pax {
     pub adults: u8,
     #[serde(default, skip_serializing_if = "is_zero")]
@@ -0,0 +1,759 @@
+//! Parser for the linear fare calculation line found in booking details,
+//! e.g. `PHX AA X/DFW AA DTT 180.93OUA0AQBN USD180.93END ZP PHXDFW XF PHX4.5`.
+
+use std::fmt::{self, Display};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{kind} at offset {position}")]
+pub struct FareCalcError {
+    /// Byte offset into the fare calculation line.
+    pub position: usize,
+    pub kind: FareCalcErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FareCalcErrorKind {
+    #[error("unexpected end of fare calculation, expected {0}")]
+    UnexpectedEnd(&'static str),
+
+    #[error("unexpected token {found:?}, expected {expected}")]
+    Unexpected {
+        found: String,
+        expected: &'static str,
+    },
+
+    #[error("fare amount with no flown legs before it")]
+    FareWithoutLegs,
+
+    #[error("legs after the last fare amount are not priced")]
+    UnpricedLegs,
+
+    #[error("unclosed side trip")]
+    UnclosedSideTrip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FareCheckError {
+    #[error("fare components sum to {components}, but the calculation totals {total}")]
+    ComponentSum { components: Decimal, total: Decimal },
+
+    #[error("no ROE to convert {currency} to {base}")]
+    MissingRoe { currency: String, base: Money },
+
+    #[error("calculation converts to {converted}, but the base fare is {base}")]
+    BaseFare { converted: Decimal, base: Money },
+}
+
+/// A fully parsed fare construction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FareConstruction {
+    pub origin: String,
+    pub components: Vec<FareComponent>,
+    /// Currency of the construction total: `NUC` for international
+    /// itineraries, the selling currency otherwise.
+    pub currency: String,
+    pub total: Decimal,
+    /// Rate of exchange from NUC to the selling currency.
+    pub roe: Option<Decimal>,
+    /// Tokens after `END` other than the ROE, typically the tax breakdown
+    /// (`ZP PHXDFW XF PHX4.5`).
+    pub trailer: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FareComponent {
+    pub origin: String,
+    pub legs: Vec<FareLeg>,
+    pub side_trips: Vec<FareComponent>,
+    pub surcharges: Vec<Surcharge>,
+    pub mileage: Vec<MileageMarker>,
+    pub amount: Decimal,
+    pub basis: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FareLeg {
+    /// `None` for a surface sector (`/-`).
+    pub carrier: Option<String>,
+    pub destination: String,
+    /// `X/`: no stopover permitted at `destination`.
+    pub connection: bool,
+    /// `O/`: explicit stopover at `destination`.
+    pub stopover: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SurchargeKind {
+    /// `Q`: fuel or other fare-level surcharge.
+    Q,
+    /// `S`: stopover charge.
+    Stopover,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Surcharge {
+    pub kind: SurchargeKind,
+    /// City pair the surcharge applies to, when given (`Q PHXDFW10.00`).
+    pub cities: Option<String>,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum MileageMarker {
+    /// `M`: fare priced by mileage.
+    Mileage,
+    /// `15M`: excess mileage surcharge, in percent.
+    ExcessMileage(u8),
+    /// `E/XXX`: extra mileage allowance via the given city.
+    ExtraAllowance(String),
+}
+
+impl FareConstruction {
+    pub fn parse(line: &str) -> Result<Self, FareCalcError> {
+        Parser::new(line).construction()
+    }
+
+    /// Fare-break cities in order, starting with the origin.
+    pub fn fare_breaks(&self) -> Vec<&str> {
+        std::iter::once(self.origin.as_str())
+            .chain(self.components.iter().map(FareComponent::destination))
+            .collect()
+    }
+
+    pub fn surcharges(&self) -> impl Iterator<Item = &Surcharge> {
+        self.components
+            .iter()
+            .flat_map(FareComponent::all_surcharges)
+    }
+
+    /// Sum of all components, surcharges and side trips, in `currency`.
+    pub fn components_total(&self) -> Decimal {
+        self.components.iter().map(FareComponent::total).sum()
+    }
+
+    /// Check that the components add up to the construction total.
+    pub fn check_total(&self) -> Result<(), FareCheckError> {
+        let components = self.components_total();
+        if components != self.total {
+            return Err(FareCheckError::ComponentSum {
+                components,
+                total: self.total,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the components against the ticket's base fare.
+    ///
+    /// NUC totals are converted with the ROE. Carriers round the converted
+    /// amount to the selling currency's unit, so a difference of less than
+    /// one whole unit is accepted.
+    pub fn verify_base_fare(&self, base: Money) -> Result<(), FareCheckError> {
+        self.check_total()?;
+
+        if self.currency == base.currency().as_str() {
+            if self.total != base.amount() {
+                return Err(FareCheckError::BaseFare {
+                    converted: self.total,
+                    base,
+                });
+            }
+            return Ok(());
+        }
+
+        let roe = self.roe.ok_or_else(|| FareCheckError::MissingRoe {
+            currency: self.currency.clone(),
+            base,
+        })?;
+        let converted = self.total * roe;
+        if (converted - base.amount()).abs() >= Decimal::ONE {
+            return Err(FareCheckError::BaseFare { converted, base });
+        }
+        Ok(())
+    }
+}
+
+impl FareComponent {
+    pub fn destination(&self) -> &str {
+        self.legs
+            .last()
+            .map_or(self.origin.as_str(), |l| l.destination.as_str())
+    }
+
+    pub fn carriers(&self) -> Vec<&str> {
+        let mut carriers: Vec<&str> = Vec::new();
+        for c in self.legs.iter().filter_map(|l| l.carrier.as_deref()) {
+            if !carriers.contains(&c) {
+                carriers.push(c);
+            }
+        }
+        carriers
+    }
+
+    pub fn surcharge_total(&self) -> Decimal {
+        self.surcharges.iter().map(|s| s.amount).sum()
+    }
+
+    /// Amount plus surcharges plus any side trips.
+    pub fn total(&self) -> Decimal {
+        self.amount
+            + self.surcharge_total()
+            + self
+                .side_trips
+                .iter()
+                .map(FareComponent::total)
+                .sum::<Decimal>()
+    }
+
+    fn all_surcharges(&self) -> Box<dyn Iterator<Item = &Surcharge> + '_> {
+        Box::new(
+            self.surcharges.iter().chain(
+                self.side_trips
+                    .iter()
+                    .flat_map(FareComponent::all_surcharges),
+            ),
+        )
+    }
+}
+
+impl Display for SurchargeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Q => f.write_str("Q"),
+            Self::Stopover => f.write_str("S"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    offset: usize,
+    text: &'a str,
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    len: usize,
+}
+
+/// Currency and amount of the `...END` total.
+type EndTotal = (String, Decimal);
+
+#[derive(Default)]
+struct Pending {
+    legs: Vec<FareLeg>,
+    side_trips: Vec<FareComponent>,
+    surcharges: Vec<Surcharge>,
+    mileage: Vec<MileageMarker>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in input.char_indices() {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                if let Some(s) = start.take() {
+                    tokens.push(Token {
+                        offset: s,
+                        text: &input[s..i],
+                    });
+                }
+                if !c.is_whitespace() {
+                    tokens.push(Token {
+                        offset: i,
+                        text: &input[i..i + 1],
+                    });
+                }
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(s) = start {
+            tokens.push(Token {
+                offset: s,
+                text: &input[s..],
+            });
+        }
+
+        Self {
+            tokens,
+            pos: 0,
+            len: input.len(),
+        }
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<Token<'a>> {
+        self.tokens.get(self.pos + n).copied()
+    }
+
+    fn next(&mut self, expected: &'static str) -> Result<Token<'a>, FareCalcError> {
+        let tok = self.peek().ok_or(FareCalcError {
+            position: self.len,
+            kind: FareCalcErrorKind::UnexpectedEnd(expected),
+        })?;
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    fn construction(mut self) -> Result<FareConstruction, FareCalcError> {
+        let origin = self.next("origin city")?;
+        let (origin, _, _) = city(origin).ok_or_else(|| unexpected(origin, "origin city"))?;
+
+        let (components, end) = self.components(origin.to_string(), false)?;
+        let (currency, total) = end.unwrap_or_default();
+
+        let mut roe = None;
+        let mut trailer = Vec::new();
+        while let Some(tok) = self.peek() {
+            self.pos += 1;
+            if let Some(rest) = tok.text.strip_prefix("ROE") {
+                let value = if rest.is_empty() {
+                    self.next("ROE value")?
+                } else {
+                    Token {
+                        offset: tok.offset + 3,
+                        text: rest,
+                    }
+                };
+                let (rate, tail) =
+                    split_amount(value.text).ok_or_else(|| unexpected(value, "ROE value"))?;
+                if !tail.is_empty() {
+                    return Err(unexpected(value, "ROE value"));
+                }
+                roe = Some(rate);
+            } else {
+                trailer.push(tok.text.to_string());
+            }
+        }
+
+        Ok(FareConstruction {
+            origin: origin.to_string(),
+            components,
+            currency,
+            total,
+            roe,
+            trailer,
+        })
+    }
+
+    /// Parse components until `END` (top level) or `)` (side trip).
+    fn components(
+        &mut self,
+        origin: String,
+        side_trip: bool,
+    ) -> Result<(Vec<FareComponent>, Option<EndTotal>), FareCalcError> {
+        let mut components = Vec::new();
+        let mut component_origin = origin.clone();
+        let mut current = origin;
+        let mut pending = Pending::default();
+
+        loop {
+            let Some(tok) = self.peek() else {
+                if side_trip {
+                    return Err(FareCalcError {
+                        position: self.len,
+                        kind: FareCalcErrorKind::UnclosedSideTrip,
+                    });
+                }
+                return Err(FareCalcError {
+                    position: self.len,
+                    kind: FareCalcErrorKind::UnexpectedEnd("END"),
+                });
+            };
+            let text = tok.text;
+
+            if text == ")" && side_trip {
+                self.pos += 1;
+                if !pending.legs.is_empty() {
+                    return Err(at(tok, FareCalcErrorKind::UnpricedLegs));
+                }
+                return Ok((components, None));
+            }
+
+            if let Some(end) = self.end_total()? {
+                if side_trip {
+                    return Err(FareCalcError {
+                        position: tok.offset,
+                        kind: FareCalcErrorKind::UnclosedSideTrip,
+                    });
+                }
+                if !pending.legs.is_empty() {
+                    return Err(at(tok, FareCalcErrorKind::UnpricedLegs));
+                }
+                return Ok((components, Some(end)));
+            }
+
+            if text == "(" {
+                self.pos += 1;
+                let (trips, _) = self.components(current.clone(), true)?;
+                pending.side_trips.extend(trips);
+                continue;
+            }
+
+            if let Some(rest) = text.strip_prefix("/-") {
+                self.pos += 1;
+                let dest = if rest.is_empty() {
+                    self.next("surface destination")?
+                } else {
+                    Token {
+                        offset: tok.offset + 2,
+                        text: rest,
+                    }
+                };
+                let (code, connection, stopover) =
+                    city(dest).ok_or_else(|| unexpected(dest, "city"))?;
+                current = code.to_string();
+                pending.legs.push(FareLeg {
+                    carrier: None,
+                    destination: current.clone(),
+                    connection,
+                    stopover,
+                });
+                continue;
+            }
+
+            if is_carrier(text)
+                && let Some(next) = self.peek_at(1)
+                && let Some((code, connection, stopover)) = city(next)
+            {
+                self.pos += 2;
+                current = code.to_string();
+                pending.legs.push(FareLeg {
+                    carrier: Some(text.to_string()),
+                    destination: current.clone(),
+                    connection,
+                    stopover,
+                });
+                continue;
+            }
+
+            if let Some(marker) = mileage_marker(text) {
+                self.pos += 1;
+                pending.mileage.push(marker);
+                continue;
+            }
+
+            if let Some(kind) = surcharge_kind(text) {
+                self.pos += 1;
+                let surcharge = self.surcharge(tok, kind)?;
+                pending.surcharges.push(surcharge);
+                continue;
+            }
+
+            let (fare_text, mileage) = match text.strip_prefix('M') {
+                Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => (rest, true),
+                _ => (text, false),
+            };
+            if let Some((amount, basis)) = split_amount(fare_text)
+                && !basis.is_empty()
+            {
+                self.pos += 1;
+                if pending.legs.is_empty() {
+                    return Err(at(tok, FareCalcErrorKind::FareWithoutLegs));
+                }
+                let mut p = std::mem::take(&mut pending);
+                if mileage {
+                    p.mileage.push(MileageMarker::Mileage);
+                }
+                components.push(FareComponent {
+                    origin: std::mem::replace(&mut component_origin, current.clone()),
+                    legs: p.legs,
+                    side_trips: p.side_trips,
+                    surcharges: p.surcharges,
+                    mileage: p.mileage,
+                    amount,
+                    basis: basis.to_string(),
+                });
+                continue;
+            }
+
+            return Err(unexpected(tok, "carrier, surcharge or fare amount"));
+        }
+    }
+
+    /// Consume `NUC361.86END` / `USD180.93 END` if it is next.
+    fn end_total(&mut self) -> Result<Option<EndTotal>, FareCalcError> {
+        let Some(tok) = self.peek() else {
+            return Ok(None);
+        };
+        let text = tok.text;
+        if !text.is_ascii() {
+            return Err(unexpected(tok, "ASCII fare calculation token"));
+        }
+        let (Some(currency), Some(amount)) = (text.get(..3), text.get(3..)) else {
+            return Ok(None);
+        };
+        if amount.is_empty() || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Ok(None);
+        }
+        let Some((total, rest)) = split_amount(amount) else {
+            return Ok(None);
+        };
+
+        match rest {
+            "END" => self.pos += 1,
+            "" if self.peek_at(1).is_some_and(|t| t.text == "END") => self.pos += 2,
+            _ => return Ok(None),
+        }
+        Ok(Some((currency.to_string(), total)))
+    }
+
+    fn surcharge(
+        &mut self,
+        tok: Token<'a>,
+        kind: SurchargeKind,
+    ) -> Result<Surcharge, FareCalcError> {
+        let body = if tok.text.len() == 1 {
+            self.next("surcharge amount")?
+        } else {
+            Token {
+                offset: tok.offset + 1,
+                text: &tok.text[1..],
+            }
+        };
+
+        let letters = body
+            .text
+            .find(|c: char| !c.is_ascii_uppercase())
+            .unwrap_or(body.text.len());
+        let (cities, amount_text) = body.text.split_at(letters);
+        if !(cities.is_empty() || cities.len() == 6) {
+            return Err(unexpected(body, "surcharge amount"));
+        }
+        let (amount, rest) =
+            split_amount(amount_text).ok_or_else(|| unexpected(body, "surcharge amount"))?;
+        if !rest.is_empty() {
+            return Err(unexpected(body, "surcharge amount"));
+        }
+
+        Ok(Surcharge {
+            kind,
+            cities: (!cities.is_empty()).then(|| cities.to_string()),
+            amount,
+        })
+    }
+}
+
+fn at(tok: Token<'_>, kind: FareCalcErrorKind) -> FareCalcError {
+    FareCalcError {
+        position: tok.offset,
+        kind,
+    }
+}
+
+fn unexpected(tok: Token<'_>, expected: &'static str) -> FareCalcError {
+    at(
+        tok,
+        FareCalcErrorKind::Unexpected {
+            found: tok.text.to_string(),
+            expected,
+        },
+    )
+}
+
+/// `DFW`, `X/DFW` or `O/DFW` → (code, connection, stopover).
+fn city(tok: Token<'_>) -> Option<(&str, bool, bool)> {
+    let (code, connection, stopover) = if let Some(rest) = tok.text.strip_prefix("X/") {
+        (rest, true, false)
+    } else if let Some(rest) = tok.text.strip_prefix("O/") {
+        (rest, false, true)
+    } else {
+        (tok.text, false, false)
+    };
+    let is_code = code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase());
+    (is_code && code != "END").then_some((code, connection, stopover))
+}
+
+fn is_carrier(text: &str) -> bool {
+    text.len() == 2
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && text.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+fn mileage_marker(text: &str) -> Option<MileageMarker> {
+    if text == "M" {
+        return Some(MileageMarker::Mileage);
+    }
+    if let Some(city) = text.strip_prefix("E/") {
+        return (city.len() == 3 && city.bytes().all(|b| b.is_ascii_uppercase()))
+            .then(|| MileageMarker::ExtraAllowance(city.to_string()));
+    }
+    let percent = text.strip_suffix('M')?;
+    percent.parse().ok().map(MileageMarker::ExcessMileage)
+}
+
+fn surcharge_kind(text: &str) -> Option<SurchargeKind> {
+    let mut chars = text.chars();
+    let kind = match chars.next()? {
+        'Q' => SurchargeKind::Q,
+        'S' => SurchargeKind::Stopover,
+        _ => return None,
+    };
+    // A bare `Q` takes its amount from the next token; otherwise the amount
+    // (or a city pair, for Q) must follow immediately.
+    match chars.next() {
+        None => Some(kind),
+        Some(c) if c.is_ascii_digit() => Some(kind),
+        Some(c) if kind == SurchargeKind::Q && c.is_ascii_uppercase() && text.len() > 7 => {
+            Some(kind)
+        }
+        _ => None,
+    }
+}
+
+/// Split a leading decimal amount off `s`, returning the remainder.
+fn split_amount(s: &str) -> Option<(Decimal, &str)> {
+    let int_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if int_end == 0 {
+        return None;
+    }
+    let mut end = int_end;
+    if let Some(frac) = s[int_end..].strip_prefix('.') {
+        let frac_len = frac
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(frac.len());
+        if frac_len > 0 {
+            end = int_end + 1 + frac_len;
+        }
+    }
+    let amount = Decimal::from_str_exact(&s[..end]).ok()?;
+    Some((amount, &s[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    #[test]
+    fn domestic_single_component() {
+        let fc = FareConstruction::parse(
+            "PHX AA X/DFW AA DTT 180.93OUA0AQBN USD180.93END ZP PHXDFW XF PHX4.5 DFW4.5",
+        )
+        .unwrap();
+
+        assert_eq!(fc.origin, "PHX");
+        assert_eq!(fc.currency, "USD");
+        assert_eq!(fc.total, dec("180.93"));
+        assert_eq!(fc.roe, None);
+        assert_eq!(fc.fare_breaks(), ["PHX", "DTT"]);
+
+        let comp = &fc.components[0];
+        assert_eq!(comp.basis, "OUA0AQBN");
+        assert_eq!(comp.carriers(), ["AA"]);
+        assert!(comp.legs[0].connection);
+        assert_eq!(fc.trailer, ["ZP", "PHXDFW", "XF", "PHX4.5", "DFW4.5"]);
+
+        fc.verify_base_fare("USD180.93".parse().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn international_with_surcharges_and_roe() {
+        let fc = FareConstruction::parse(
+            "LON BA NYC Q10.00 M500.00YOW BA X/CHI BA LON Q LONCHI5.00 S25.00 600.00YOW \
+             NUC1140.00END ROE0.750000",
+        )
+        .unwrap();
+
+        assert_eq!(fc.components.len(), 2);
+        assert_eq!(fc.components[0].mileage, [MileageMarker::Mileage]);
+        assert_eq!(fc.components[1].origin, "NYC");
+        assert_eq!(
+            fc.components[1].surcharges[0].cities.as_deref(),
+            Some("LONCHI")
+        );
+        assert_eq!(fc.surcharges().count(), 3);
+        assert_eq!(fc.roe, Some(dec("0.750000")));
+
+        fc.check_total().unwrap();
+        fc.verify_base_fare("GBP855".parse().unwrap()).unwrap();
+        assert!(matches!(
+            fc.verify_base_fare("GBP900".parse().unwrap()),
+            Err(FareCheckError::BaseFare { .. })
+        ));
+    }
+
+    #[test]
+    fn side_trip_and_mileage_markers() {
+        let fc = FareConstruction::parse(
+            "NYC BA LON (BA PAR 100.00Y BA LON 100.00Y) E/MAN 10M BA NYC 800.00Y \
+             NUC1000.00 END ROE1.00",
+        )
+        .unwrap();
+
+        let comp = &fc.components[0];
+        assert_eq!(comp.side_trips.len(), 2);
+        assert_eq!(comp.side_trips[0].origin, "LON");
+        assert_eq!(comp.side_trips[0].destination(), "PAR");
+        assert_eq!(
+            comp.mileage,
+            [
+                MileageMarker::ExtraAllowance("MAN".to_string()),
+                MileageMarker::ExcessMileage(10)
+            ]
+        );
+        assert_eq!(comp.total(), dec("1000.00"));
+        fc.check_total().unwrap();
+    }
+
+    #[test]
+    fn surface_sector() {
+        let fc = FareConstruction::parse("SFO UA NYC /-BOS UA SFO 450.00K USD450.00END").unwrap();
+        let legs = &fc.components[0].legs;
+        assert_eq!(legs[1].carrier, None);
+        assert_eq!(legs[1].destination, "BOS");
+    }
+
+    #[test]
+    fn component_mismatch_detected() {
+        let fc = FareConstruction::parse("PHX AA DFW 100.00Y USD120.00END").unwrap();
+        assert!(matches!(
+            fc.check_total(),
+            Err(FareCheckError::ComponentSum { .. })
+        ));
+    }
+
+    #[test]
+    fn errors_report_position() {
+        let err = FareConstruction::parse("PHX AA DFW ??? USD1END").unwrap_err();
+        assert_eq!(err.position, 11);
+
+        let err = FareConstruction::parse("PHX AA DFW").unwrap_err();
+        assert_eq!(err.kind, FareCalcErrorKind::UnexpectedEnd("END"));
+
+        let err = FareConstruction::parse("PHX AA DFW USD100END").unwrap_err();
+        assert_eq!(err.kind, FareCalcErrorKind::UnpricedLegs);
+    }
+
+    #[test]
+    fn non_ascii_tokens_are_errors() {
+        for line in [
+            "PHX AA DFW éé USD1END",
+            "PHX AA DFW 100.00Y USD1é0END",
+            "PHX AA DFW 100.00Y ÜSD100.00END",
+        ] {
+            let err = FareConstruction::parse(line).unwrap_err();
+            assert!(
+                matches!(err.kind, FareCalcErrorKind::Unexpected { .. }),
+                "{line}: {err:?}"
+            );
+        }
+    }
+}
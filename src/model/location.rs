@@ -1,4 +1,8 @@
-use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::reference::AirportDb;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,21 +19,198 @@ pub struct Location {
     pub lat_lng: Option<LatLng>,
     #[serde(rename = "type")]
     #[serde(default)]
-    pub location_type: Option<String>,
+    pub location_type: Option<LocationKind>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Location {
+    pub fn is_city(&self) -> bool {
+        self.location_type
+            .as_ref()
+            .is_some_and(LocationKind::is_city)
+    }
+
+    /// Codes to use as search origins or destinations. Cities expand to
+    /// their airports in the embedded airport table when asked, and stay as
+    /// the city code if none are known.
+    pub fn search_codes(&self, expansion: CityExpansion) -> Vec<String> {
+        if expansion == CityExpansion::Airports && self.is_city() {
+            let airports: Vec<_> = AirportDb::embedded()
+                .city(&self.code)
+                .map(|a| a.code.clone())
+                .collect();
+            if !airports.is_empty() {
+                return airports;
+            }
+        }
+        vec![self.code.clone()]
+    }
+}
+
+/// The `type` of a [`Location`]. Deserialization ignores case, `_`, `-` and
+/// spaces, and keeps anything unrecognized in `Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocationKind {
+    Airport,
+    City,
+    MultiAirportCity,
+    Unknown(String),
+}
+
+impl LocationKind {
+    pub fn parse(s: &str) -> Self {
+        let key: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match key.as_str() {
+            "airport" => Self::Airport,
+            "city" => Self::City,
+            "multiairportcity" => Self::MultiAirportCity,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Airport => "airport",
+            Self::City => "city",
+            Self::MultiAirportCity => "multiAirportCity",
+            Self::Unknown(s) => s,
+        }
+    }
+
+    pub fn is_city(&self) -> bool {
+        matches!(self, Self::City | Self::MultiAirportCity)
+    }
+}
+
+impl Display for LocationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for LocationKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LocationKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::parse(&s))
+    }
+}
+
+/// How [`Location::search_codes`] treats cities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CityExpansion {
+    /// Search the city code and let the API pick the airports.
+    #[default]
+    KeepCity,
+    /// Search each airport of the city.
+    Airports,
+}
+
+/// A city with the airports under it, or a lone airport.
+#[derive(Debug, Clone)]
+pub struct LocationGroup {
+    /// City code, or the airport code for a lone airport.
+    pub code: String,
+    /// The city entry itself, when the results included one.
+    pub city: Option<Location>,
+    pub airports: Vec<Location>,
+}
+
+impl LocationGroup {
+    /// Codes to search for the group: the city code, or its airports.
+    pub fn search_codes(&self, expansion: CityExpansion) -> Vec<String> {
+        match (expansion, &self.city) {
+            (CityExpansion::Airports, _) if !self.airports.is_empty() => {
+                self.airports.iter().map(|a| a.code.clone()).collect()
+            }
+            (_, Some(city)) => city.search_codes(expansion),
+            (_, None) => vec![self.code.clone()],
+        }
+    }
+}
+
+impl Display for LocationGroup {
+    /// `NYC (JFK, LGA, EWR)`, or just `JFK` for a lone airport.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code)?;
+        let lone =
+            self.city.is_none() && matches!(self.airports.as_slice(), [a] if a.code == self.code);
+        if !lone && !self.airports.is_empty() {
+            let codes: Vec<_> = self.airports.iter().map(|a| a.code.as_str()).collect();
+            write!(f, " ({})", codes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Group airports under their city, in order of first appearance. Airports
+/// whose city is not in the list are grouped by `city_code`; a city of one
+/// airport with the same code (e.g. `PHX`) stays a lone airport.
+pub fn group_by_city(locations: Vec<Location>) -> Vec<LocationGroup> {
+    let mut groups: Vec<LocationGroup> = Vec::new();
+    for location in locations {
+        let code = if location.is_city() {
+            location.code.clone()
+        } else {
+            location
+                .city_code
+                .clone()
+                .unwrap_or_else(|| location.code.clone())
+        };
+        let index = match groups.iter().position(|g| g.code == code) {
+            Some(index) => index,
+            None => {
+                groups.push(LocationGroup {
+                    code,
+                    city: None,
+                    airports: Vec::new(),
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        if location.is_city() {
+            group.city.get_or_insert(location);
+        } else {
+            group.airports.push(location);
+        }
+    }
+    groups
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct LatLng {
     pub latitude: f64,
     pub longitude: f64,
 }
 
+impl LatLng {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    /// Great-circle distance by the haversine formula.
+    pub fn distance_km(&self, other: &LatLng) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct LocationsResponse {
     pub locations: Vec<Location>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Currency {
     pub code: String,
@@ -40,3 +221,63 @@ pub struct Currency {
 pub struct CurrenciesResponse {
     pub currencies: Vec<Currency>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locations() -> Vec<Location> {
+        serde_json::from_value(serde_json::json!([
+            { "code": "NYC", "displayName": "New York", "type": "multiAirportCity" },
+            { "code": "JFK", "displayName": "Kennedy", "cityCode": "NYC", "type": "airport" },
+            { "code": "PHX", "displayName": "Phoenix", "cityCode": "PHX", "type": "AIRPORT" },
+            { "code": "LGA", "displayName": "LaGuardia", "cityCode": "NYC", "type": "airport" },
+            { "code": "EWR", "displayName": "Newark", "cityCode": "NYC", "type": "airport" },
+            { "code": "XYZ", "displayName": "Somewhere", "type": "heliport" },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn kinds_parse_leniently() {
+        let kinds: Vec<_> = locations().into_iter().map(|l| l.location_type).collect();
+        assert_eq!(kinds[0], Some(LocationKind::MultiAirportCity));
+        assert_eq!(kinds[2], Some(LocationKind::Airport));
+        assert_eq!(
+            kinds[5],
+            Some(LocationKind::Unknown("heliport".to_string()))
+        );
+        assert_eq!(
+            LocationKind::parse("multi_airport_city"),
+            LocationKind::MultiAirportCity
+        );
+        assert_eq!(
+            serde_json::to_value(LocationKind::MultiAirportCity).unwrap(),
+            "multiAirportCity"
+        );
+    }
+
+    #[test]
+    fn groups_airports_under_city() {
+        let groups = group_by_city(locations());
+        let shown: Vec<_> = groups.iter().map(ToString::to_string).collect();
+        assert_eq!(shown, ["NYC (JFK, LGA, EWR)", "PHX", "XYZ"]);
+        assert_eq!(groups[0].search_codes(CityExpansion::KeepCity), ["NYC"]);
+        assert_eq!(
+            groups[0].search_codes(CityExpansion::Airports),
+            ["JFK", "LGA", "EWR"]
+        );
+    }
+
+    #[test]
+    fn city_expands_from_airport_table() {
+        let city = &locations()[0];
+        assert_eq!(city.search_codes(CityExpansion::KeepCity), ["NYC"]);
+        assert_eq!(
+            city.search_codes(CityExpansion::Airports),
+            ["JFK", "LGA", "EWR"]
+        );
+        let airport = &locations()[1];
+        assert_eq!(airport.search_codes(CityExpansion::Airports), ["JFK"]);
+    }
+}
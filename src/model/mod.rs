@@ -1,6 +1,9 @@
 pub mod common;
+pub mod fare_calc;
 pub mod location;
+pub mod money;
 pub mod response;
 pub mod routing;
+pub mod rules;
 pub mod search;
 pub mod summarize;
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use super::location::Currency;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MoneyError {
+    #[error("empty price string")]
+    Empty,
+
+    #[error("invalid currency code: {0:?}")]
+    InvalidCurrency(String),
+
+    #[error("unknown currency code: {0}")]
+    UnknownCurrency(CurrencyCode),
+
+    #[error("invalid amount: {0:?}")]
+    InvalidAmount(String),
+
+    #[error("currency mismatch: {left} vs {right}")]
+    CurrencyMismatch {
+        left: CurrencyCode,
+        right: CurrencyCode,
+    },
+}
+
+/// ISO 4217 alphabetic currency code, e.g. `USD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CurrencyCode([u8; 3]);
+
+impl CurrencyCode {
+    pub fn new(code: &str) -> Result<Self, MoneyError> {
+        match code.as_bytes() {
+            &[a, b, c] if code.bytes().all(|b| b.is_ascii_uppercase()) => Ok(Self([a, b, c])),
+            _ => Err(MoneyError::InvalidCurrency(code.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever constructed from three ASCII uppercase bytes.
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    /// Whether this code appears in a list returned by `ItaClient::currencies()`.
+    pub fn is_listed(&self, currencies: &[Currency]) -> bool {
+        currencies.iter().any(|c| c.code == self.as_str())
+    }
+}
+
+impl Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// An exact decimal amount in a single currency.
+///
+/// Ordering compares the currency code first and the amount second, so a
+/// list of prices in one currency sorts by amount. Use [`Money::checked_cmp`]
+/// when mixed currencies must be reported rather than ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    amount: Decimal,
+    currency: CurrencyCode,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: CurrencyCode) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn zero(currency: CurrencyCode) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn currency(&self) -> CurrencyCode {
+        self.currency
+    }
+
+    /// Parse an ITA price string such as `USD123.45`, `USD 1,234.56` or
+    /// `123.45 EUR`.
+    pub fn parse(s: &str) -> Result<Self, MoneyError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(MoneyError::Empty);
+        }
+
+        let (code, amount) = if s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let split = s
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(s.len());
+            s.split_at(split)
+        } else {
+            let split = s
+                .char_indices()
+                .rfind(|(_, c)| !c.is_ascii_alphabetic())
+                .map_or(0, |(i, c)| i + c.len_utf8());
+            let (amount, code) = s.split_at(split);
+            (code, amount)
+        };
+
+        let currency = CurrencyCode::new(code.trim())?;
+        let digits: String = amount.trim().chars().filter(|c| *c != ',').collect();
+        let amount = Decimal::from_str_exact(&digits)
+            .map_err(|_| MoneyError::InvalidAmount(amount.trim().to_string()))?;
+
+        Ok(Self { amount, currency })
+    }
+
+    /// Parse and additionally require the currency to be one ITA knows about.
+    pub fn parse_known(s: &str, currencies: &[Currency]) -> Result<Self, MoneyError> {
+        let money = Self::parse(s)?;
+        if !money.currency.is_listed(currencies) {
+            return Err(MoneyError::UnknownCurrency(money.currency));
+        }
+        Ok(money)
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, MoneyError> {
+        self.same_currency(&other)?;
+        Ok(Self::new(self.amount + other.amount, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self, MoneyError> {
+        self.same_currency(&other)?;
+        Ok(Self::new(self.amount - other.amount, self.currency))
+    }
+
+    pub fn checked_cmp(&self, other: &Self) -> Result<Ordering, MoneyError> {
+        self.same_currency(other)?;
+        Ok(self.amount.cmp(&other.amount))
+    }
+
+    /// Sum an iterator of prices, failing on the first currency mismatch.
+    /// Returns `Ok(None)` for an empty iterator.
+    pub fn try_sum<I: IntoIterator<Item = Self>>(iter: I) -> Result<Option<Self>, MoneyError> {
+        let mut iter = iter.into_iter();
+        let Some(first) = iter.next() else {
+            return Ok(None);
+        };
+        iter.try_fold(first, Self::checked_add).map(Some)
+    }
+
+    /// The lowest of an iterator of prices, failing on the first currency
+    /// mismatch. Returns `Ok(None)` for an empty iterator.
+    pub fn try_min<I: IntoIterator<Item = Self>>(iter: I) -> Result<Option<Self>, MoneyError> {
+        let mut iter = iter.into_iter();
+        let Some(first) = iter.next() else {
+            return Ok(None);
+        };
+        iter.try_fold(first, |lowest, price| {
+            Ok(match lowest.checked_cmp(&price)? {
+                Ordering::Greater => price,
+                _ => lowest,
+            })
+        })
+        .map(Some)
+    }
+
+    fn same_currency(&self, other: &Self) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                left: self.currency,
+                right: other.currency,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Money {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.currency
+            .cmp(&other.currency)
+            .then_with(|| self.amount.cmp(&other.amount))
+    }
+}
+
+impl Display for Money {
+    /// Formats in ITA's compact style (`USD123.45`). A precision, as in
+    /// `{:.2}`, rounds the amount.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(dp) => {
+                let rounded = self.amount.round_dp(u32::try_from(dp).unwrap_or(u32::MAX));
+                write!(f, "{}{:.*}", self.currency, dp, rounded)
+            }
+            None => write!(f, "{}{}", self.currency, self.amount),
+        }
+    }
+}
+
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserialize an optional price, treating a value that isn't a parseable
+/// price string as absent so one odd field doesn't fail a whole response.
+/// Use with `#[serde(default, deserialize_with = "...")]`.
+pub fn lenient<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::String(s)) => match Money::parse(&s) {
+            Ok(money) => Some(money),
+            Err(error) => {
+                tracing::debug!(price = %s, %error, "ignoring unparseable price");
+                None
+            }
+        },
+        Some(serde_json::Value::Null) | None => None,
+        Some(other) => {
+            tracing::debug!(price = %other, "ignoring non-string price");
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(s: &str) -> Money {
+        Money::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_ita_formats() {
+        let m = usd("USD123.45");
+        assert_eq!(m.currency().as_str(), "USD");
+        assert_eq!(m.amount(), Decimal::new(12345, 2));
+
+        assert_eq!(usd("USD 1,234.56").amount(), Decimal::new(123456, 2));
+        assert_eq!(usd("123.45 EUR").currency().as_str(), "EUR");
+        assert_eq!(usd("JPY12000").amount(), Decimal::new(12000, 0));
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        assert_eq!(Money::parse(""), Err(MoneyError::Empty));
+        assert!(matches!(
+            Money::parse("US123"),
+            Err(MoneyError::InvalidCurrency(_))
+        ));
+        assert!(matches!(
+            Money::parse("USD12x"),
+            Err(MoneyError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            Money::parse("50€"),
+            Err(MoneyError::InvalidCurrency(_))
+        ));
+        assert!(matches!(
+            Money::parse("€50"),
+            Err(MoneyError::InvalidCurrency(_))
+        ));
+    }
+
+    #[test]
+    fn parse_known_checks_currency_list() {
+        let currencies = vec![Currency {
+            code: "USD".to_string(),
+            display_name: "US Dollar".to_string(),
+        }];
+        assert!(Money::parse_known("USD10", &currencies).is_ok());
+        assert!(matches!(
+            Money::parse_known("EUR10", &currencies),
+            Err(MoneyError::UnknownCurrency(_))
+        ));
+    }
+
+    #[test]
+    fn arithmetic_and_ordering() {
+        let sum = usd("USD100.10").checked_add(usd("USD0.90")).unwrap();
+        assert_eq!(sum, usd("USD101"));
+        assert_eq!(sum.to_string(), "USD101.00");
+
+        let mut prices = [usd("USD300"), usd("USD99.99"), usd("USD150.5")];
+        prices.sort();
+        assert_eq!(prices[0], usd("USD99.99"));
+
+        assert!(matches!(
+            usd("USD1").checked_add(usd("EUR1")),
+            Err(MoneyError::CurrencyMismatch { .. })
+        ));
+        assert_eq!(
+            Money::try_sum([usd("USD1"), usd("USD2.50")]).unwrap(),
+            Some(usd("USD3.50"))
+        );
+        assert_eq!(
+            Money::try_min([usd("USD3"), usd("USD1"), usd("USD2")]).unwrap(),
+            Some(usd("USD1"))
+        );
+        assert!(Money::try_min([usd("USD100"), usd("EUR900")]).is_err());
+    }
+
+    #[test]
+    fn display_and_serde_round_trip() {
+        let m = usd("USD123.456");
+        assert_eq!(format!("{m:.2}"), "USD123.46");
+
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, "\"USD123.456\"");
+        let back: Money = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, m);
+    }
+
+    #[test]
+    fn lenient_fields_drop_bad_prices() {
+        #[derive(Deserialize)]
+        struct Prices {
+            #[serde(default, deserialize_with = "lenient")]
+            good: Option<Money>,
+            #[serde(default, deserialize_with = "lenient")]
+            bad: Option<Money>,
+            #[serde(default, deserialize_with = "lenient")]
+            number: Option<Money>,
+            #[serde(default, deserialize_with = "lenient")]
+            null: Option<Money>,
+            #[serde(default, deserialize_with = "lenient")]
+            missing: Option<Money>,
+        }
+
+        let prices: Prices = serde_json::from_str(
+            r#"{"good": "USD10", "bad": "ten dollars", "number": 10, "null": null}"#,
+        )
+        .unwrap();
+        assert_eq!(prices.good, Some(usd("USD10")));
+        assert_eq!(prices.bad, None);
+        assert_eq!(prices.number, None);
+        assert_eq!(prices.null, None);
+        assert_eq!(prices.missing, None);
+    }
+}
@@ -1,6 +1,10 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::fare_calc::{FareCalcError, FareConstruction};
+use super::money::{Money, MoneyError};
+
 /// Top-level booking details response wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,12 +20,12 @@ pub struct BookingDetails {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BookingExt {
-    #[serde(default)]
-    pub total_price: Option<String>,
-    #[serde(default)]
-    pub price: Option<String>,
-    #[serde(default)]
-    pub price_per_mile: Option<String>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub total_price: Option<Money>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub price: Option<Money>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub price_per_mile: Option<Money>,
     /// Catch-all for extra fields
     #[serde(flatten)]
     pub extra: Value,
@@ -103,6 +107,9 @@ pub struct Segment {
     pub duration: Option<u32>,
     #[serde(default)]
     pub flight_number: Option<String>,
+    /// The airline flying the segment, when the response reports it.
+    #[serde(default)]
+    pub operating_carrier: Option<Carrier>,
     #[serde(default)]
     pub aircraft: Option<Aircraft>,
     #[serde(default)]
@@ -156,11 +163,164 @@ pub struct Ticket {
     pub extra: Value,
 }
 
+impl Ticket {
+    /// Taxes for every passenger on the ticket, summed per tax code in the
+    /// order they first appear.
+    pub fn tax_breakdown(&self) -> Result<Vec<Tax>, MoneyError> {
+        let mut totals: Vec<Tax> = Vec::new();
+        for pricing in &self.pricings {
+            let pax = Decimal::from(pricing.pax_count.unwrap_or(1));
+            for tax in &pricing.taxes {
+                let Some(amount) = tax.amount else { continue };
+                let amount = Money::new(amount.amount() * pax, amount.currency());
+
+                match totals.iter_mut().find(|t| t.code == tax.code) {
+                    Some(total) => {
+                        total.amount = Some(match total.amount {
+                            Some(sum) => sum.checked_add(amount)?,
+                            None => amount,
+                        });
+                    }
+                    None => totals.push(Tax {
+                        amount: Some(amount),
+                        ..tax.clone()
+                    }),
+                }
+            }
+        }
+        Ok(totals)
+    }
+}
+
+/// Pricing for one passenger type on a ticket. Amounts are per passenger;
+/// multiply by `pax_count` for the group total.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Pricing {
     #[serde(default)]
     pub pax_count: Option<u32>,
+    /// Passenger type code, e.g. `ADT`, `CNN`, `INF`.
+    #[serde(default)]
+    pub ptc: Option<String>,
+    #[serde(default)]
+    pub fares: Vec<Fare>,
+    #[serde(default)]
+    pub taxes: Vec<Tax>,
+    #[serde(default)]
+    pub fare_calculations: Vec<FareCalculation>,
+    #[serde(
+        default,
+        rename = "displayFareTotal",
+        alias = "saleFareTotal",
+        deserialize_with = "super::money::lenient"
+    )]
+    pub fare_total: Option<Money>,
+    #[serde(
+        default,
+        rename = "displayTaxTotal",
+        alias = "saleTaxTotal",
+        deserialize_with = "super::money::lenient"
+    )]
+    pub tax_total: Option<Money>,
+    #[serde(
+        default,
+        rename = "displayPrice",
+        alias = "salePriceTotal",
+        deserialize_with = "super::money::lenient"
+    )]
+    pub total: Option<Money>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+impl Pricing {
+    /// The linear fare calculation, with ITA's display line breaks removed.
+    pub fn fare_calculation_line(&self) -> Option<String> {
+        let lines: Vec<&str> = self
+            .fare_calculations
+            .iter()
+            .flat_map(|fc| fc.lines.iter())
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        (!lines.is_empty()).then(|| lines.join(" "))
+    }
+
+    pub fn fare_construction(&self) -> Option<Result<FareConstruction, FareCalcError>> {
+        self.fare_calculation_line()
+            .map(|line| FareConstruction::parse(&line))
+    }
+
+    pub fn fare_basis_codes(&self) -> Vec<&str> {
+        self.fares
+            .iter()
+            .filter_map(|f| f.basis.as_deref())
+            .collect()
+    }
+
+    /// Sum of the individual taxes, for cross-checking `tax_total`.
+    pub fn taxes_sum(&self) -> Result<Option<Money>, MoneyError> {
+        Money::try_sum(self.taxes.iter().filter_map(|t| t.amount))
+    }
+}
+
+/// One fare component: a priced journey between two fare-break cities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fare {
+    #[serde(default)]
+    pub carrier: Option<String>,
+    #[serde(default, rename = "originCity")]
+    pub origin: Option<String>,
+    #[serde(default, rename = "destinationCity")]
+    pub destination: Option<String>,
+    #[serde(default, rename = "code")]
+    pub basis: Option<String>,
+    #[serde(
+        default,
+        rename = "displayAdjustedPrice",
+        alias = "displayPrice",
+        deserialize_with = "super::money::lenient"
+    )]
+    pub amount: Option<Money>,
+    #[serde(default)]
+    pub ptc: Option<String>,
+    #[serde(default)]
+    pub booking_infos: Vec<BookingInfo>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+impl Fare {
+    pub fn booking_codes(&self) -> Vec<&str> {
+        self.booking_infos
+            .iter()
+            .filter_map(|b| b.booking_code.as_deref())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tax {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(
+        default,
+        rename = "displayPrice",
+        deserialize_with = "super::money::lenient"
+    )]
+    pub amount: Option<Money>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FareCalculation {
+    #[serde(default)]
+    pub lines: Vec<String>,
     #[serde(flatten)]
     pub extra: Value,
 }
@@ -191,8 +351,69 @@ pub struct CarrierStopRow {
 pub struct StopCell {
     #[serde(default)]
     pub count: Option<u32>,
-    #[serde(default)]
-    pub min_price: Option<String>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub min_price: Option<Money>,
     #[serde(flatten)]
     pub extra: Value,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICKET_JSON: &str = r#"{
+        "pricings": [{
+            "paxCount": 2,
+            "ptc": "ADT",
+            "fares": [{
+                "carrier": "AA",
+                "originCity": "PHX",
+                "destinationCity": "DTT",
+                "code": "OUA0AQBN",
+                "displayAdjustedPrice": "USD180.93",
+                "ptc": "ADT",
+                "bookingInfos": [{ "bookingCode": "O", "cabin": "COACH" }]
+            }],
+            "taxes": [
+                { "code": "US", "name": "US Transportation Tax", "displayPrice": "USD13.57" },
+                { "code": "ZP", "name": "US Flight Segment Tax", "displayPrice": "USD5.20" },
+                { "code": "XF", "name": "US Passenger Facility Charge", "displayPrice": "USD4.50" }
+            ],
+            "fareCalculations": [{ "lines": ["PHX AA X/DFW AA DTT 180.93OUA0AQBN", "USD180.93END ZP PHXDFW XF PHX4.5"] }],
+            "displayFareTotal": "USD180.93",
+            "displayTaxTotal": "USD23.27",
+            "displayPrice": "USD204.20"
+        }]
+    }"#;
+
+    #[test]
+    fn ticket_typed_pricing() {
+        let ticket: Ticket = serde_json::from_str(TICKET_JSON).unwrap();
+        let pricing = &ticket.pricings[0];
+
+        assert_eq!(pricing.ptc.as_deref(), Some("ADT"));
+        assert_eq!(pricing.fare_basis_codes(), ["OUA0AQBN"]);
+        assert_eq!(pricing.fares[0].booking_codes(), ["O"]);
+        assert_eq!(pricing.fares[0].destination.as_deref(), Some("DTT"));
+        assert_eq!(pricing.taxes_sum().unwrap(), pricing.tax_total);
+        assert_eq!(
+            pricing.fare_calculation_line().as_deref(),
+            Some("PHX AA X/DFW AA DTT 180.93OUA0AQBN USD180.93END ZP PHXDFW XF PHX4.5")
+        );
+
+        let construction = pricing.fare_construction().unwrap().unwrap();
+        construction
+            .verify_base_fare(pricing.fare_total.unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn tax_breakdown_multiplies_by_pax() {
+        let ticket: Ticket = serde_json::from_str(TICKET_JSON).unwrap();
+        let breakdown = ticket.tax_breakdown().unwrap();
+
+        assert_eq!(breakdown.len(), 3);
+        assert_eq!(breakdown[0].code.as_deref(), Some("US"));
+        assert_eq!(breakdown[0].amount, Some("USD27.14".parse().unwrap()));
+    }
+}
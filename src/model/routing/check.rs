@@ -0,0 +1,545 @@
+//! Offline checking of extension codes against booking details.
+
+use std::fmt::{self, Display};
+
+use super::ExtensionCode;
+use super::command::{AircraftSpec, ExtensionCommand, TimeSpan};
+use crate::model::response::{Airport, BookingDetails, ItinerarySlice, Segment};
+use crate::reference::EquipmentDb;
+
+/// A command the slice fails, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub slice: usize,
+    pub command: ExtensionCommand,
+    pub reason: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slice {}: {}: {}",
+            self.slice + 1,
+            self.command,
+            self.reason
+        )
+    }
+}
+
+/// Result of [`ExtensionCode::check`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    pub violations: Vec<Violation>,
+    /// Commands that could not be checked: unsupported offline, or the
+    /// response lacks the data (e.g. operating carriers for `-CODESHARE`).
+    pub unchecked: Vec<ExtensionCommand>,
+}
+
+impl CheckReport {
+    /// No violations. Unchecked commands are not counted against the slice.
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    fn skip(&mut self, command: &ExtensionCommand) {
+        if !self.unchecked.contains(command) {
+            self.unchecked.push(command.clone());
+        }
+    }
+}
+
+impl ExtensionCode {
+    /// Check every slice of the booking against the code without calling
+    /// the API.
+    pub fn check(&self, details: &BookingDetails) -> CheckReport {
+        let slices = details
+            .itinerary
+            .as_ref()
+            .map(|it| it.slices.as_slice())
+            .unwrap_or_default();
+        let mut report = CheckReport::default();
+        for (i, slice) in slices.iter().enumerate() {
+            self.check_into(i, slice, &mut report);
+        }
+        report
+    }
+
+    pub fn check_slice(&self, slice: &ItinerarySlice) -> CheckReport {
+        let mut report = CheckReport::default();
+        self.check_into(0, slice, &mut report);
+        report
+    }
+
+    fn check_into(&self, index: usize, slice: &ItinerarySlice, report: &mut CheckReport) {
+        for command in self.commands() {
+            match check_command(command, slice) {
+                Outcome::Pass => {}
+                Outcome::Fail(reason) => report.violations.push(Violation {
+                    slice: index,
+                    command: command.clone(),
+                    reason,
+                }),
+                Outcome::Unchecked => report.skip(command),
+            }
+        }
+    }
+}
+
+enum Outcome {
+    Pass,
+    Fail(String),
+    Unchecked,
+}
+
+impl Outcome {
+    fn first_failure(reasons: impl IntoIterator<Item = Option<String>>) -> Self {
+        let mut unchecked = false;
+        for reason in reasons {
+            match reason {
+                Some(reason) => return Self::Fail(reason),
+                None => unchecked = true,
+            }
+        }
+        if unchecked {
+            Self::Unchecked
+        } else {
+            Self::Pass
+        }
+    }
+}
+
+fn check_command(command: &ExtensionCommand, slice: &ItinerarySlice) -> Outcome {
+    let segments = &slice.segments;
+    if segments.is_empty() {
+        return Outcome::Unchecked;
+    }
+
+    match command {
+        ExtensionCommand::MaxStops(max) => {
+            let stops = segments.len() - 1;
+            if stops as u32 > *max {
+                let plural = if stops == 1 { "" } else { "s" };
+                Outcome::Fail(format!("{stops} stop{plural}"))
+            } else {
+                Outcome::Pass
+            }
+        }
+        ExtensionCommand::MaxDuration(max) => match slice.duration {
+            Some(minutes) if minutes > max.total_minutes() => {
+                Outcome::Fail(format!("takes {}", TimeSpan::from_minutes(minutes)))
+            }
+            Some(_) => Outcome::Pass,
+            None => Outcome::Unchecked,
+        },
+        ExtensionCommand::MinConnection(min) => check_connections(segments, |m| {
+            (m < min.total_minutes()).then_some("shorter than allowed")
+        }),
+        ExtensionCommand::MaxConnection(max) => check_connections(segments, |m| {
+            (m > max.total_minutes()).then_some("longer than allowed")
+        }),
+        ExtensionCommand::NoRedeyes => {
+            let mut unknown = false;
+            for segment in segments {
+                match is_redeye(segment) {
+                    Some(true) => {
+                        return Outcome::Fail(format!("{} is a red-eye", flight(segment)));
+                    }
+                    Some(false) => {}
+                    None => unknown = true,
+                }
+            }
+            if unknown {
+                Outcome::Unchecked
+            } else {
+                Outcome::Pass
+            }
+        }
+        ExtensionCommand::NoOvernights => {
+            let mut unknown = false;
+            for pair in segments.windows(2) {
+                let arrive = pair[0].arrival.as_deref().and_then(local_date);
+                let depart = pair[1].departure.as_deref().and_then(local_date);
+                match (arrive, depart) {
+                    (Some(a), Some(d)) if a != d => {
+                        return Outcome::Fail(format!(
+                            "overnight connection at {}",
+                            code(pair[0].destination.as_ref())
+                        ));
+                    }
+                    (Some(_), Some(_)) => {}
+                    _ => unknown = true,
+                }
+            }
+            if unknown {
+                Outcome::Unchecked
+            } else {
+                Outcome::Pass
+            }
+        }
+        ExtensionCommand::Aircraft(specs) => {
+            check_aircraft(segments, specs, |listed| (!listed).then_some("is on"))
+        }
+        ExtensionCommand::ExcludeAircraft(specs) => {
+            check_aircraft(segments, specs, |listed| listed.then_some("is on excluded"))
+        }
+        ExtensionCommand::Airlines(codes) => check_carriers(segments, |carrier| {
+            (!codes.iter().any(|c| c == carrier)).then_some("not an allowed airline")
+        }),
+        ExtensionCommand::ExcludeAirlines(codes) => check_carriers(segments, |carrier| {
+            codes
+                .iter()
+                .any(|c| c == carrier)
+                .then_some("an excluded airline")
+        }),
+        ExtensionCommand::ExcludeCities(codes) => {
+            Outcome::first_failure(segments[..segments.len() - 1].iter().filter_map(|segment| {
+                match &segment.destination {
+                    Some(airport) if codes.iter().any(|c| matches_city(airport, c)) => {
+                        Some(Some(format!("connects at {}", code(Some(airport)))))
+                    }
+                    Some(Airport { code: Some(_), .. }) => None,
+                    _ => Some(None),
+                }
+            }))
+        }
+        ExtensionCommand::NoCodeshare => {
+            Outcome::first_failure(segments.iter().filter_map(|segment| {
+                let marketing = segment.carrier.as_ref().and_then(|c| c.code.as_deref());
+                let operating = segment
+                    .operating_carrier
+                    .as_ref()
+                    .and_then(|c| c.code.as_deref());
+                match (marketing, operating) {
+                    (Some(m), Some(o)) if m == o => None,
+                    (Some(_), Some(o)) => {
+                        Some(Some(format!("{} is operated by {o}", flight(segment))))
+                    }
+                    _ => Some(None),
+                }
+            }))
+        }
+        ExtensionCommand::OperatingAirlines(codes) => check_operating(segments, |carrier| {
+            (!codes.iter().any(|c| c == carrier)).then_some("not an allowed airline")
+        }),
+        ExtensionCommand::ExcludeOperatingAirlines(codes) => check_operating(segments, |carrier| {
+            codes
+                .iter()
+                .any(|c| c == carrier)
+                .then_some("an excluded airline")
+        }),
+        // Distances, alliances, equipment categories and fare details are
+        // not in the booking details this checks against.
+        ExtensionCommand::MaxMiles(_)
+        | ExtensionCommand::MinMiles(_)
+        | ExtensionCommand::Alliances(_)
+        | ExtensionCommand::NoProps
+        | ExtensionCommand::NoFirstClass
+        | ExtensionCommand::RequireCabin(_)
+        | ExtensionCommand::ExcludeCabin(_)
+        | ExtensionCommand::BookingClass(_)
+        | ExtensionCommand::FareBasis(_)
+        | ExtensionCommand::Raw(_) => Outcome::Unchecked,
+    }
+}
+
+fn check_connections(segments: &[Segment], fails: impl Fn(u32) -> Option<&'static str>) -> Outcome {
+    Outcome::first_failure(segments[..segments.len() - 1].iter().filter_map(|segment| {
+        match segment.connection.as_ref().and_then(|c| c.duration) {
+            Some(minutes) => fails(minutes).map(|why| {
+                Some(format!(
+                    "{} connection at {} is {why}",
+                    TimeSpan::from_minutes(minutes),
+                    code(segment.destination.as_ref())
+                ))
+            }),
+            None => Some(None),
+        }
+    }))
+}
+
+fn check_carriers(segments: &[Segment], fails: impl Fn(&str) -> Option<&'static str>) -> Outcome {
+    Outcome::first_failure(segments.iter().filter_map(|segment| {
+        match segment.carrier.as_ref().and_then(|c| c.code.as_deref()) {
+            Some(carrier) => fails(carrier).map(|why| Some(format!("{carrier} is {why}"))),
+            None => Some(None),
+        }
+    }))
+}
+
+fn check_operating(segments: &[Segment], fails: impl Fn(&str) -> Option<&'static str>) -> Outcome {
+    Outcome::first_failure(segments.iter().filter_map(|segment| {
+        match segment
+            .operating_carrier
+            .as_ref()
+            .and_then(|c| c.code.as_deref())
+        {
+            Some(carrier) => fails(carrier).map(|why| {
+                Some(format!(
+                    "{} is operated by {carrier}, {why}",
+                    flight(segment)
+                ))
+            }),
+            None => Some(None),
+        }
+    }))
+}
+
+/// Aircraft type checks; any category spec leaves the command unchecked.
+fn check_aircraft(
+    segments: &[Segment],
+    specs: &[AircraftSpec],
+    fails: impl Fn(bool) -> Option<&'static str>,
+) -> Outcome {
+    let mut types = Vec::new();
+    for spec in specs {
+        match spec {
+            AircraftSpec::Type(code) => types.push(code.as_str()),
+            AircraftSpec::Category(_) => return Outcome::Unchecked,
+        }
+    }
+    Outcome::first_failure(segments.iter().filter_map(|segment| {
+        match segment.aircraft.as_ref().and_then(|a| a.code.as_deref()) {
+            Some(code) => match listed(&types, code) {
+                Some(listed) => {
+                    fails(listed).map(|why| Some(format!("{} {why} {code}", flight(segment))))
+                }
+                None => Some(None),
+            },
+            None => Some(None),
+        }
+    }))
+}
+
+/// Whether the aircraft is one of `types`, expanding generic family codes
+/// such as `787` through the equipment table. `None` when that can't be
+/// told: a listed code is not in the table, or the segment only reports a
+/// family that a listed type belongs to.
+fn listed(types: &[&str], aircraft: &str) -> Option<bool> {
+    let db = EquipmentDb::embedded();
+    let family = |code: &str| {
+        db.by_iata(code)
+            .filter(|t| t.icao.is_none())
+            .map(|t| t.family.as_str())
+    };
+    let flown = db.by_iata(aircraft);
+    let mut unknown = false;
+    for &code in types {
+        if code.eq_ignore_ascii_case(aircraft) {
+            return Some(true);
+        }
+        let Some(info) = db.by_iata(code) else {
+            unknown = true;
+            continue;
+        };
+        match (family(code), flown) {
+            (Some(family), Some(flown)) if flown.family.eq_ignore_ascii_case(family) => {
+                return Some(true);
+            }
+            (Some(_), None) => unknown = true,
+            (None, Some(flown)) if flown.icao.is_none() && flown.family == info.family => {
+                unknown = true;
+            }
+            _ => {}
+        }
+    }
+    (!unknown).then_some(false)
+}
+
+fn matches_city(airport: &Airport, code: &str) -> bool {
+    airport.code.as_deref() == Some(code)
+        || airport.city.as_ref().and_then(|c| c.code.as_deref()) == Some(code)
+}
+
+fn code(airport: Option<&Airport>) -> &str {
+    airport.and_then(|a| a.code.as_deref()).unwrap_or("?")
+}
+
+fn flight(segment: &Segment) -> String {
+    let carrier = segment
+        .carrier
+        .as_ref()
+        .and_then(|c| c.code.as_deref())
+        .unwrap_or_default();
+    format!(
+        "{carrier}{}",
+        segment.flight_number.as_deref().unwrap_or_default()
+    )
+}
+
+/// `YYYY-MM-DD` of a local ISO-8601 timestamp.
+fn local_date(timestamp: &str) -> Option<&str> {
+    timestamp
+        .get(..10)
+        .filter(|d| d.as_bytes().get(4) == Some(&b'-'))
+}
+
+/// Local hour of an ISO-8601 timestamp.
+fn local_hour(timestamp: &str) -> Option<u32> {
+    timestamp.get(11..13)?.parse().ok()
+}
+
+/// A flight that lands on a later local date than it left, before 10:00.
+/// This is a heuristic: without time zones the time aloft cannot be placed
+/// at night exactly.
+fn is_redeye(segment: &Segment) -> Option<bool> {
+    let departure = segment.departure.as_deref()?;
+    let arrival = segment.arrival.as_deref()?;
+    Some(local_date(arrival)? > local_date(departure)? && local_hour(arrival)? < 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details() -> BookingDetails {
+        serde_json::from_value(serde_json::json!({
+            "itinerary": { "slices": [{
+                "duration": 900,
+                "segments": [
+                    {
+                        "carrier": { "code": "AA" },
+                        "flightNumber": "100",
+                        "origin": { "code": "LAX" },
+                        "destination": { "code": "ORD", "city": { "code": "CHI" } },
+                        "departure": "2025-03-01T22:30-08:00",
+                        "arrival": "2025-03-02T04:40-06:00",
+                        "aircraft": { "code": "321" },
+                        "connection": { "duration": 50 }
+                    },
+                    {
+                        "carrier": { "code": "AA" },
+                        "flightNumber": "200",
+                        "origin": { "code": "ORD" },
+                        "destination": { "code": "BOS" },
+                        "departure": "2025-03-02T05:30-06:00",
+                        "arrival": "2025-03-02T08:45-05:00",
+                        "aircraft": { "code": "738" }
+                    }
+                ]
+            }]}
+        }))
+        .unwrap()
+    }
+
+    fn failed(code: &str) -> Vec<String> {
+        ExtensionCode::parse(code)
+            .unwrap()
+            .check(&details())
+            .violations
+            .iter()
+            .map(|v| v.reason.clone())
+            .collect()
+    }
+
+    #[test]
+    fn passing_constraints() {
+        let report = ExtensionCode::parse("MAXSTOPS 1; MAXDUR 15:00; AIRLINES AA UA; -OVERNIGHTS")
+            .unwrap()
+            .check(&details());
+        assert!(report.passed(), "{:?}", report.violations);
+        assert!(report.unchecked.is_empty());
+    }
+
+    #[test]
+    fn failing_constraints() {
+        assert_eq!(failed("MAXSTOPS 0"), ["1 stop"]);
+        assert_eq!(failed("MAXDUR 14:59"), ["takes 15:00"]);
+        assert_eq!(
+            failed("MINCONNECT 1:00"),
+            ["0:50 connection at ORD is shorter than allowed"]
+        );
+        assert!(failed("MAXCONNECT 0:50").is_empty());
+        assert_eq!(failed("-REDEYES"), ["AA100 is a red-eye"]);
+        assert_eq!(failed("AIRCRAFT T:321"), ["AA200 is on 738"]);
+        assert_eq!(failed("-AIRCRAFT T:738"), ["AA200 is on excluded 738"]);
+        assert_eq!(failed("-AIRCRAFT T:737"), ["AA200 is on excluded 738"]);
+        assert_eq!(failed("-CITIES CHI"), ["connects at ORD"]);
+        assert_eq!(failed("-AIRLINES AA"), ["AA is an excluded airline"]);
+    }
+
+    #[test]
+    fn unknown_data_is_unchecked() {
+        let report = ExtensionCode::parse("-CODESHARE; OPAIRLINES AA; AIRCRAFT C:JET")
+            .unwrap()
+            .check(&details());
+        assert!(report.passed());
+        assert_eq!(report.unchecked.len(), 3);
+    }
+
+    #[test]
+    fn operating_carriers_are_checked_when_reported() {
+        let mut details = details();
+        let slice = &mut details.itinerary.as_mut().unwrap().slices[0];
+        for (segment, operator) in slice.segments.iter_mut().zip(["AA", "MQ"]) {
+            segment.operating_carrier = serde_json::from_value(serde_json::json!({
+                "code": operator
+            }))
+            .unwrap();
+        }
+        let failed = |code: &str| {
+            let report = ExtensionCode::parse(code).unwrap().check(&details);
+            assert!(report.unchecked.is_empty(), "{code}");
+            report
+                .violations
+                .into_iter()
+                .map(|v| v.reason)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(failed("-CODESHARE"), ["AA200 is operated by MQ"]);
+        assert_eq!(
+            failed("OPAIRLINES AA"),
+            ["AA200 is operated by MQ, not an allowed airline"]
+        );
+        assert_eq!(
+            failed("-OPAIRLINES MQ"),
+            ["AA200 is operated by MQ, an excluded airline"]
+        );
+        assert!(failed("OPAIRLINES AA MQ").is_empty());
+    }
+
+    #[test]
+    fn missing_connection_airport_is_unchecked() {
+        let mut details = details();
+        details.itinerary.as_mut().unwrap().slices[0].segments[0].destination = None;
+        let report = ExtensionCode::parse("-CITIES DFW").unwrap().check(&details);
+        assert!(report.passed());
+        assert_eq!(report.unchecked.len(), 1);
+    }
+
+    #[test]
+    fn aircraft_families_expand() {
+        let mut details = details();
+        details.itinerary.as_mut().unwrap().slices[0]
+            .segments
+            .truncate(1);
+        slice_aircraft(&mut details, "789");
+        let check = |code: &str| ExtensionCode::parse(code).unwrap().check(&details);
+
+        let report = check("AIRCRAFT T:787");
+        assert!(report.passed() && report.unchecked.is_empty(), "{report:?}");
+        assert_eq!(
+            check("AIRCRAFT T:350").violations[0].reason,
+            "AA100 is on 789"
+        );
+
+        // A code the table doesn't know might be a family containing 789.
+        let report = check("AIRCRAFT T:XYZ");
+        assert!(report.passed());
+        assert_eq!(report.unchecked.len(), 1);
+
+        // A generic 787 might or might not be the listed 789.
+        slice_aircraft(&mut details, "787");
+        let report = ExtensionCode::parse("AIRCRAFT T:789")
+            .unwrap()
+            .check(&details);
+        assert!(report.passed());
+        assert_eq!(report.unchecked.len(), 1);
+    }
+
+    fn slice_aircraft(details: &mut BookingDetails, code: &str) {
+        let segment = &mut details.itinerary.as_mut().unwrap().slices[0].segments[0];
+        segment.aircraft = serde_json::from_value(serde_json::json!({ "code": code })).unwrap();
+    }
+}
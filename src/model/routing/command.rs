@@ -0,0 +1,130 @@
+use std::fmt::{self, Display};
+
+use super::{AircraftCategory, Alliance, CabinClass};
+
+/// An `h:mm` duration as written in extension codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeSpan {
+    hours: u32,
+    minutes: u32,
+}
+
+impl TimeSpan {
+    /// Returns `None` unless `minutes < 60` and the total number of minutes
+    /// fits in a `u32`.
+    pub fn new(hours: u32, minutes: u32) -> Option<Self> {
+        let fits = hours.checked_mul(60).and_then(|m| m.checked_add(minutes));
+        (minutes < 60 && fits.is_some()).then_some(Self { hours, minutes })
+    }
+
+    pub fn from_minutes(total: u32) -> Self {
+        Self {
+            hours: total / 60,
+            minutes: total % 60,
+        }
+    }
+
+    pub fn hours(self) -> u32 {
+        self.hours
+    }
+
+    pub fn minutes(self) -> u32 {
+        self.minutes
+    }
+
+    pub fn total_minutes(self) -> u32 {
+        self.hours * 60 + self.minutes
+    }
+}
+
+impl Display for TimeSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{:02}", self.hours, self.minutes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AircraftSpec {
+    /// `T:787`
+    Type(String),
+    /// `C:JET`
+    Category(AircraftCategory),
+}
+
+impl Display for AircraftSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Type(code) => write!(f, "T:{code}"),
+            Self::Category(category) => write!(f, "C:{category}"),
+        }
+    }
+}
+
+/// One `;`-separated command of an extension code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionCommand {
+    NoCodeshare,
+    MaxStops(u32),
+    MaxDuration(TimeSpan),
+    MaxMiles(u32),
+    MinMiles(u32),
+    MinConnection(TimeSpan),
+    MaxConnection(TimeSpan),
+    Alliances(Vec<Alliance>),
+    Airlines(Vec<String>),
+    ExcludeAirlines(Vec<String>),
+    OperatingAirlines(Vec<String>),
+    ExcludeOperatingAirlines(Vec<String>),
+    ExcludeCities(Vec<String>),
+    NoRedeyes,
+    NoOvernights,
+    Aircraft(Vec<AircraftSpec>),
+    ExcludeAircraft(Vec<AircraftSpec>),
+    NoProps,
+    NoFirstClass,
+    RequireCabin(CabinClass),
+    ExcludeCabin(CabinClass),
+    BookingClass(String),
+    FareBasis(String),
+    /// Passed through unvalidated by `ExtensionCode::raw`.
+    Raw(String),
+}
+
+fn join<T: Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Display for ExtensionCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCodeshare => f.write_str("-CODESHARE"),
+            Self::MaxStops(n) => write!(f, "MAXSTOPS {n}"),
+            Self::MaxDuration(t) => write!(f, "MAXDUR {t}"),
+            Self::MaxMiles(n) => write!(f, "MAXMILES {n}"),
+            Self::MinMiles(n) => write!(f, "MINMILES {n}"),
+            Self::MinConnection(t) => write!(f, "MINCONNECT {t}"),
+            Self::MaxConnection(t) => write!(f, "MAXCONNECT {t}"),
+            Self::Alliances(a) => write!(f, "ALLIANCE {}", join(a)),
+            Self::Airlines(c) => write!(f, "AIRLINES {}", c.join(" ")),
+            Self::ExcludeAirlines(c) => write!(f, "-AIRLINES {}", c.join(" ")),
+            Self::OperatingAirlines(c) => write!(f, "OPAIRLINES {}", c.join(" ")),
+            Self::ExcludeOperatingAirlines(c) => write!(f, "-OPAIRLINES {}", c.join(" ")),
+            Self::ExcludeCities(c) => write!(f, "-CITIES {}", c.join(" ")),
+            Self::NoRedeyes => f.write_str("-REDEYES"),
+            Self::NoOvernights => f.write_str("-OVERNIGHTS"),
+            Self::Aircraft(specs) => write!(f, "AIRCRAFT {}", join(specs)),
+            Self::ExcludeAircraft(specs) => write!(f, "-AIRCRAFT {}", join(specs)),
+            Self::NoProps => f.write_str("-PROPS"),
+            Self::NoFirstClass => f.write_str("-NOFIRSTCLASS"),
+            Self::RequireCabin(c) => write!(f, "+CABIN {c}"),
+            Self::ExcludeCabin(c) => write!(f, "-CABIN {c}"),
+            Self::BookingClass(code) => write!(f, "F BC={code}"),
+            Self::FareBasis(basis) => write!(f, "F ..{basis}"),
+            Self::Raw(raw) => f.write_str(raw),
+        }
+    }
+}
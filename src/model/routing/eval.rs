@@ -0,0 +1,480 @@
+//! Offline matching of routing codes against itineraries.
+//!
+//! Flight terms (`C:AA`, `F:UA45`, `N`, ...) each consume one flight; point
+//! terms (`X`, `X:ORD`, `ORD,DFW`) check the connection airport between the
+//! flights either side of them without consuming anything. The code must
+//! account for every flight in the slice.
+
+use std::fmt::{self, Display};
+
+use thiserror::Error;
+
+use super::term::{Selector, Term, TermKind};
+use super::{ParseError, RoutingCode};
+use crate::model::response::{Airport, ItinerarySlice, Segment};
+use crate::model::search::SolutionSlice;
+
+/// One flight of a slice, reduced to the fields routing codes look at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlightLeg {
+    pub carrier: Option<String>,
+    /// Taken from the segment when the response reports it; set it by hand
+    /// otherwise to evaluate `O:` terms.
+    pub operating_carrier: Option<String>,
+    pub number: Option<u32>,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+}
+
+impl From<&Segment> for FlightLeg {
+    fn from(segment: &Segment) -> Self {
+        Self {
+            carrier: segment.carrier.as_ref().and_then(|c| c.code.clone()),
+            operating_carrier: segment
+                .operating_carrier
+                .as_ref()
+                .and_then(|c| c.code.clone()),
+            number: segment.flight_number.as_deref().and_then(flight_number),
+            origin: airport_code(segment.origin.as_ref()),
+            destination: airport_code(segment.destination.as_ref()),
+        }
+    }
+}
+
+/// The flights of one slice, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SliceView {
+    pub legs: Vec<FlightLeg>,
+}
+
+impl From<&ItinerarySlice> for SliceView {
+    fn from(slice: &ItinerarySlice) -> Self {
+        Self {
+            legs: slice.segments.iter().map(FlightLeg::from).collect(),
+        }
+    }
+}
+
+impl From<&SolutionSlice> for SliceView {
+    /// Uses `segments` when present, otherwise rebuilds the legs from the
+    /// `flights` and `stops` summary.
+    fn from(slice: &SolutionSlice) -> Self {
+        if !slice.segments.is_empty() {
+            return Self {
+                legs: slice.segments.iter().map(FlightLeg::from).collect(),
+            };
+        }
+
+        // Connection airports are only trustworthy when there is exactly
+        // one stop between each pair of flights.
+        let n = slice.flights.len();
+        let stops_line_up = slice.stops.len() + 1 == n;
+        let point = |i: usize| -> Option<String> {
+            if i == 0 {
+                airport_code(slice.origin.as_ref())
+            } else if i == n {
+                airport_code(slice.destination.as_ref())
+            } else if stops_line_up {
+                airport_code(slice.stops.get(i - 1))
+            } else {
+                None
+            }
+        };
+
+        let legs = slice
+            .flights
+            .iter()
+            .enumerate()
+            .map(|(i, flight)| {
+                let flight = flight.trim();
+                let split = flight
+                    .char_indices()
+                    .skip(2)
+                    .find(|(_, c)| c.is_ascii_digit())
+                    .map_or(flight.len(), |(at, _)| at);
+                let (carrier, number) = flight.split_at(split);
+                FlightLeg {
+                    carrier: (!carrier.is_empty()).then(|| carrier.to_ascii_uppercase()),
+                    operating_carrier: None,
+                    number: flight_number(number),
+                    origin: point(i),
+                    destination: point(i + 1),
+                }
+            })
+            .collect();
+        Self { legs }
+    }
+}
+
+fn airport_code(airport: Option<&Airport>) -> Option<String> {
+    airport.and_then(|a| a.code.clone())
+}
+
+/// The numeric part of `"123"`, `"AA123"` or `"AA 0123"`.
+fn flight_number(s: &str) -> Option<u32> {
+    let digits = s.trim_start_matches(|c: char| !c.is_ascii_digit());
+    digits.parse().ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EvalError {
+    #[error("raw fragment does not parse: {0}")]
+    Parse(#[from] ParseError),
+
+    #[error("flight {leg} has no {field}, needed by {term}")]
+    MissingData {
+        leg: usize,
+        field: &'static str,
+        term: Term,
+    },
+}
+
+/// Outcome of [`RoutingCode::evaluate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Match,
+    Mismatch(Mismatch),
+}
+
+impl Verdict {
+    pub fn is_match(&self) -> bool {
+        matches!(self, Self::Match)
+    }
+}
+
+/// The point where matching got furthest before failing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Index of the failing term, `None` when all terms were used up.
+    pub term_index: Option<usize>,
+    pub term: Option<Term>,
+    /// Number of flights matched before the failure.
+    pub leg: usize,
+    pub reason: MismatchReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MismatchReason {
+    /// The flight at `leg` does not satisfy the term.
+    Flight,
+    /// The airport after flight `leg` does not satisfy the term.
+    Connection(String),
+    /// A point term found the origin or destination instead of a connection.
+    NotAConnection,
+    /// The term needs another flight but the slice has ended.
+    OutOfFlights,
+    /// Every term matched but flights remain.
+    ExtraFlights,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flight = self.leg + 1;
+        let term = self
+            .term
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        match &self.reason {
+            MismatchReason::Flight => write!(f, "flight {flight} does not match {term}"),
+            MismatchReason::Connection(airport) => {
+                write!(f, "connection at {airport} does not match {term}")
+            }
+            MismatchReason::NotAConnection => {
+                write!(f, "{term} needs a connection after flight {}", self.leg)
+            }
+            MismatchReason::OutOfFlights => {
+                write!(f, "{term} needs more than {} flights", self.leg)
+            }
+            MismatchReason::ExtraFlights => {
+                write!(f, "routing ends after {} flights, slice has more", self.leg)
+            }
+        }
+    }
+}
+
+impl RoutingCode {
+    /// Check a slice against the code without calling the API.
+    ///
+    /// `raw()` fragments are parsed first and fail with
+    /// [`EvalError::Parse`]; terms that need data the slice lacks fail with
+    /// [`EvalError::MissingData`]. `N` is treated as any single flight, since
+    /// technical stops are not reported.
+    pub fn evaluate(&self, slice: impl Into<SliceView>) -> Result<Verdict, EvalError> {
+        let terms = self.terms()?;
+        let view = slice.into();
+        let mut matcher = Matcher {
+            terms: &terms,
+            legs: &view.legs,
+            furthest: None,
+        };
+        if matcher.run(0, 0)? {
+            return Ok(Verdict::Match);
+        }
+        Ok(Verdict::Mismatch(
+            matcher
+                .furthest
+                .expect("a failed match records its failure"),
+        ))
+    }
+}
+
+struct Matcher<'a> {
+    terms: &'a [Term],
+    legs: &'a [FlightLeg],
+    furthest: Option<Mismatch>,
+}
+
+impl Matcher<'_> {
+    /// Whether `terms[ti..]` matches `legs[k..]`.
+    fn run(&mut self, ti: usize, k: usize) -> Result<bool, EvalError> {
+        let n = self.legs.len();
+        let Some(term) = self.terms.get(ti) else {
+            if k == n {
+                return Ok(true);
+            }
+            self.fail(None, k, MismatchReason::ExtraFlights);
+            return Ok(false);
+        };
+        let (min, max) = term.quantifier.map_or((1, Some(1)), |q| q.bounds());
+
+        if !term.is_flight() {
+            if min == 0 && self.run(ti + 1, k)? {
+                return Ok(true);
+            }
+            if k == 0 || k == n {
+                self.fail(Some(ti), k, MismatchReason::NotAConnection);
+                return Ok(false);
+            }
+            let airports = self.connection(k);
+            if airports.is_empty() {
+                return Err(missing(k, "connection airport", term));
+            }
+            if airports.iter().any(|a| point_matches(term, a)) {
+                return self.run(ti + 1, k);
+            }
+            self.fail(Some(ti), k, MismatchReason::Connection(airports.join("/")));
+            return Ok(false);
+        }
+
+        let mut pos = k;
+        let mut count = 0;
+        loop {
+            if count >= min && self.run(ti + 1, pos)? {
+                return Ok(true);
+            }
+            if max == Some(count) {
+                return Ok(false);
+            }
+            let Some(leg) = self.legs.get(pos) else {
+                self.fail(Some(ti), pos, MismatchReason::OutOfFlights);
+                return Ok(false);
+            };
+            if !flight_matches(term, leg, pos)? {
+                self.fail(Some(ti), pos, MismatchReason::Flight);
+                return Ok(false);
+            }
+            pos += 1;
+            count += 1;
+        }
+    }
+
+    /// Airports between flight `k - 1` and flight `k`; two when the
+    /// connection changes airport.
+    fn connection(&self, k: usize) -> Vec<&str> {
+        let mut airports = Vec::new();
+        for code in [&self.legs[k - 1].destination, &self.legs[k].origin]
+            .into_iter()
+            .flatten()
+        {
+            if !airports.contains(&code.as_str()) {
+                airports.push(code.as_str());
+            }
+        }
+        airports
+    }
+
+    /// Keep the failure that got furthest into the slice, preferring a
+    /// failing term over "flights remain" at the same flight.
+    fn fail(&mut self, ti: Option<usize>, leg: usize, reason: MismatchReason) {
+        let further = match &self.furthest {
+            None => true,
+            Some(m) => (leg, ti.map_or(0, |t| t + 1)) > (m.leg, m.term_index.map_or(0, |t| t + 1)),
+        };
+        if further {
+            self.furthest = Some(Mismatch {
+                term_index: ti,
+                term: ti.map(|t| self.terms[t].clone()),
+                leg,
+                reason,
+            });
+        }
+    }
+}
+
+fn missing(leg: usize, field: &'static str, term: &Term) -> EvalError {
+    EvalError::MissingData {
+        leg,
+        field,
+        term: term.clone(),
+    }
+}
+
+fn flight_matches(term: &Term, leg: &FlightLeg, index: usize) -> Result<bool, EvalError> {
+    let carrier = || {
+        leg.carrier
+            .as_deref()
+            .ok_or_else(|| missing(index, "carrier", term))
+    };
+    match &term.kind {
+        TermKind::AnyFlight | TermKind::Nonstop(None) => Ok(true),
+        TermKind::Nonstop(Some(code)) => Ok(carrier()? == code),
+        TermKind::AnyConnection => Ok(false),
+        TermKind::Match { negated, selector } => {
+            let hit = match selector {
+                Selector::Carrier(code) => carrier()? == code,
+                Selector::Carriers(codes) => {
+                    let carrier = carrier()?;
+                    codes.iter().any(|c| c == carrier)
+                }
+                Selector::OperatingCarrier(code) => {
+                    leg.operating_carrier
+                        .as_deref()
+                        .ok_or_else(|| missing(index, "operating carrier", term))?
+                        == code
+                }
+                Selector::Flight {
+                    carrier: code,
+                    number,
+                } => {
+                    let flight = leg
+                        .number
+                        .ok_or_else(|| missing(index, "flight number", term))?;
+                    carrier()? == code && flight == *number
+                }
+                Selector::ConnectionAt(_) | Selector::Airports(_) => false,
+            };
+            Ok(hit != *negated)
+        }
+    }
+}
+
+fn point_matches(term: &Term, airport: &str) -> bool {
+    match &term.kind {
+        TermKind::AnyConnection => true,
+        TermKind::Match { negated, selector } => {
+            let hit = match selector {
+                Selector::ConnectionAt(code) => code == airport,
+                Selector::Airports(codes) => codes.iter().any(|c| c == airport),
+                _ => false,
+            };
+            hit != *negated
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(flight: &str, origin: &str, destination: &str) -> FlightLeg {
+        FlightLeg {
+            carrier: Some(flight[..2].to_string()),
+            operating_carrier: None,
+            number: flight_number(flight),
+            origin: Some(origin.to_string()),
+            destination: Some(destination.to_string()),
+        }
+    }
+
+    fn view(legs: &[FlightLeg]) -> SliceView {
+        SliceView {
+            legs: legs.to_vec(),
+        }
+    }
+
+    fn eval(code: &str, slice: &SliceView) -> Verdict {
+        RoutingCode::parse(code)
+            .unwrap()
+            .evaluate(slice.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn carriers_and_connection() {
+        let slice = view(&[
+            leg("AA100", "BOS", "DFW"),
+            leg("AA200", "DFW", "ORD"),
+            leg("UA300", "ORD", "SFO"),
+        ]);
+        assert!(eval("C:AA+ X:ORD C:UA+", &slice).is_match());
+        assert!(eval("F+", &slice).is_match());
+        assert!(eval("~C:NK+", &slice).is_match());
+        assert!(eval("C:AA X C:AA X:ORD F:UA300", &slice).is_match());
+        assert!(!eval("C:AA+ X:DFW C:UA+", &slice).is_match());
+        assert!(!eval("N", &slice).is_match());
+    }
+
+    #[test]
+    fn explains_furthest_failure() {
+        let slice = view(&[leg("AA100", "BOS", "DFW"), leg("NK200", "DFW", "LAX")]);
+        let Verdict::Mismatch(m) = eval("~C:NK+", &slice) else {
+            panic!("expected a mismatch");
+        };
+        assert_eq!(m.leg, 1);
+        assert_eq!(m.reason, MismatchReason::Flight);
+        assert_eq!(m.to_string(), "flight 2 does not match ~C:NK+");
+
+        let Verdict::Mismatch(m) = eval("C:AA X:ORD F", &slice) else {
+            panic!("expected a mismatch");
+        };
+        assert_eq!(m.reason, MismatchReason::Connection("DFW".to_string()));
+
+        let Verdict::Mismatch(m) = eval("C:AA", &slice) else {
+            panic!("expected a mismatch");
+        };
+        assert_eq!(m.reason, MismatchReason::ExtraFlights);
+    }
+
+    #[test]
+    fn airport_change_matches_either_airport() {
+        let slice = view(&[leg("DL1", "ATL", "LGA"), leg("DL2", "JFK", "LHR")]);
+        assert!(eval("F X:JFK F", &slice).is_match());
+        assert!(eval("F LGA F", &slice).is_match());
+    }
+
+    #[test]
+    fn missing_data_and_raw_errors() {
+        let slice = view(&[leg("AA100", "BOS", "DFW")]);
+        let err = RoutingCode::parse("O:AA")
+            .unwrap()
+            .evaluate(slice.clone())
+            .unwrap_err();
+        assert!(matches!(err, EvalError::MissingData { leg: 0, .. }));
+
+        let err = RoutingCode::new().raw("C:A!").evaluate(slice).unwrap_err();
+        assert!(matches!(err, EvalError::Parse(_)));
+    }
+
+    #[test]
+    fn solution_slice_from_flights_and_stops() {
+        let slice: SolutionSlice = serde_json::from_value(serde_json::json!({
+            "origin": { "code": "BOS" },
+            "destination": { "code": "SFO" },
+            "flights": ["AA100", "UA300"],
+            "stops": [{ "code": "ORD" }],
+        }))
+        .unwrap();
+        let view = SliceView::from(&slice);
+        assert_eq!(view.legs[0], leg("AA100", "BOS", "ORD"));
+        assert_eq!(view.legs[1], leg("UA300", "ORD", "SFO"));
+        assert!(
+            RoutingCode::parse("C:AA X:ORD C:UA")
+                .unwrap()
+                .evaluate(&slice)
+                .unwrap()
+                .is_match()
+        );
+    }
+}
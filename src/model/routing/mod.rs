@@ -0,0 +1,901 @@
+mod check;
+mod command;
+mod eval;
+mod parse;
+mod term;
+
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub use check::{CheckReport, Violation};
+pub use command::{AircraftSpec, ExtensionCommand, TimeSpan};
+pub use eval::{EvalError, FlightLeg, Mismatch, MismatchReason, SliceView, Verdict};
+pub use parse::{ParseError, ParseErrorKind};
+pub use term::{Quantifier, Selector, Term, TermKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Alliance {
+    #[serde(rename = "oneworld")]
+    OneWorld,
+    #[serde(rename = "skyteam")]
+    SkyTeam,
+    #[serde(rename = "star-alliance")]
+    StarAlliance,
+}
+
+impl Alliance {
+    /// Parse the extension-code spelling, case-insensitively.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "oneworld" => Some(Self::OneWorld),
+            "skyteam" => Some(Self::SkyTeam),
+            "star-alliance" => Some(Self::StarAlliance),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Alliance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OneWorld => f.write_str("oneworld"),
+            Self::SkyTeam => f.write_str("skyteam"),
+            Self::StarAlliance => f.write_str("star-alliance"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CabinClass {
+    First,
+    Business,
+    PremiumEconomy,
+    Economy,
+}
+
+impl CabinClass {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "1" => Some(Self::First),
+            "2" => Some(Self::Business),
+            "pe" => Some(Self::PremiumEconomy),
+            "3" => Some(Self::Economy),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CabinClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => f.write_char('1'),
+            Self::Business => f.write_char('2'),
+            Self::PremiumEconomy => f.write_str("pe"),
+            Self::Economy => f.write_char('3'),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AircraftCategory {
+    Jet,
+    Turboprop,
+    Piston,
+    Train,
+    Helicopter,
+    Amphibian,
+    Surface,
+}
+
+impl AircraftCategory {
+    pub const ALL: [Self; 7] = [
+        Self::Jet,
+        Self::Turboprop,
+        Self::Piston,
+        Self::Train,
+        Self::Helicopter,
+        Self::Amphibian,
+        Self::Surface,
+    ];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.to_string().eq_ignore_ascii_case(code))
+    }
+}
+
+impl Display for AircraftCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Jet => "JET",
+            Self::Turboprop => "TURBOPROP",
+            Self::Piston => "PISTON",
+            Self::Train => "TRAIN",
+            Self::Helicopter => "HELICOPTER",
+            Self::Amphibian => "AMPHIBIAN",
+            Self::Surface => "SURFACE",
+        };
+        f.write_str(s)
+    }
+}
+
+pub mod aircraft {
+    pub const AIRBUS_A320_FAMILY: &str = "32S";
+    pub const AIRBUS_A320NEO: &str = "32N";
+    pub const AIRBUS_A321NEO: &str = "32Q";
+    pub const AIRBUS_A330: &str = "330";
+    pub const AIRBUS_A350: &str = "350";
+    pub const AIRBUS_A380: &str = "380";
+    pub const BOEING_737: &str = "737";
+    pub const BOEING_737_MAX: &str = "7M8";
+    pub const BOEING_747: &str = "747";
+    pub const BOEING_757: &str = "757";
+    pub const BOEING_767: &str = "767";
+    pub const BOEING_777: &str = "777";
+    pub const BOEING_787: &str = "787";
+    pub const EMBRAER_E175: &str = "E75";
+    pub const EMBRAER_E190: &str = "E90";
+    pub const BOMBARDIER_CRJ: &str = "CRJ";
+    pub const DASH_8: &str = "DH8";
+}
+
+/// One entry of a [`RoutingCode`]: a typed term, or an unvalidated fragment
+/// added with [`RoutingCode::raw`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoutingItem {
+    Term(Term),
+    Raw(String),
+}
+
+impl Display for RoutingItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Term(term) => write!(f, "{term}"),
+            Self::Raw(raw) => f.write_str(raw),
+        }
+    }
+}
+
+/// A routing code as a sequence of typed terms.
+///
+/// Repetition and exclusion are set on the [`Term`] before it is added, as in
+/// `.term(Selector::carrier("AA").exclude())` or
+/// `.term(Term::any_flight().optional())`, so a modifier can't end up with
+/// nothing to apply to. Codes passed to the builder methods are taken as
+/// given (empty ones are caught by `debug_assert!` only); call
+/// [`RoutingCode::validate`] to check the finished code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingCode {
+    items: Vec<RoutingItem>,
+}
+
+impl RoutingCode {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse and normalize a routing code, e.g. `"c:aa+  X:ORD"` becomes
+    /// `"C:AA+ X:ORD"`.
+    pub fn parse(code: &str) -> Result<Self, ParseError> {
+        Ok(Self::from_terms(parse::parse_routing(code)?))
+    }
+
+    pub fn from_terms(terms: impl IntoIterator<Item = Term>) -> Self {
+        Self {
+            items: terms.into_iter().map(RoutingItem::Term).collect(),
+        }
+    }
+
+    pub fn items(&self) -> &[RoutingItem] {
+        &self.items
+    }
+
+    /// The code as typed terms. Fails if a `raw()` fragment does not parse.
+    pub fn terms(&self) -> Result<Vec<Term>, ParseError> {
+        let mut terms = Vec::new();
+        for item in &self.items {
+            match item {
+                RoutingItem::Term(term) => terms.push(term.clone()),
+                RoutingItem::Raw(raw) => terms.extend(parse::parse_routing(raw)?),
+            }
+        }
+        Ok(terms)
+    }
+
+    #[must_use]
+    pub fn term(mut self, term: impl Into<Term>) -> Self {
+        self.items.push(RoutingItem::Term(term.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn nonstop(self) -> Self {
+        self.term(Term::nonstop())
+    }
+
+    #[must_use]
+    pub fn nonstop_on(self, carrier: &str) -> Self {
+        debug_assert!(!carrier.is_empty(), "carrier must not be empty");
+        self.term(Term::nonstop_on(carrier))
+    }
+
+    #[must_use]
+    pub fn carrier(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "carrier code must not be empty");
+        self.term(Selector::carrier(code))
+    }
+
+    #[must_use]
+    pub fn carrier_one_or_more(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "carrier code must not be empty");
+        self.term(Term::from(Selector::carrier(code)).one_or_more())
+    }
+
+    #[must_use]
+    pub fn carriers(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "carrier codes must not be empty");
+        self.term(Selector::carriers(codes))
+    }
+
+    #[must_use]
+    pub fn operating_carrier(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "carrier code must not be empty");
+        self.term(Selector::operating_carrier(code))
+    }
+
+    #[must_use]
+    pub fn connection(self) -> Self {
+        self.term(Term::any_connection())
+    }
+
+    #[must_use]
+    pub fn connection_at(self, airport: &str) -> Self {
+        debug_assert!(!airport.is_empty(), "airport code must not be empty");
+        self.term(Selector::connection_at(airport))
+    }
+
+    #[must_use]
+    pub fn connections_at(self, airports: &[&str]) -> Self {
+        debug_assert!(!airports.is_empty(), "airport codes must not be empty");
+        self.term(Selector::airports(airports))
+    }
+
+    #[must_use]
+    pub fn flight(self, carrier: &str, number: u32) -> Self {
+        debug_assert!(!carrier.is_empty(), "carrier code must not be empty");
+        self.term(Selector::flight(carrier, number))
+    }
+
+    #[must_use]
+    pub fn any_flight(self) -> Self {
+        self.term(Term::any_flight())
+    }
+
+    #[must_use]
+    pub fn exclude_carrier(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "carrier code must not be empty");
+        self.term(Selector::carrier(code).exclude())
+    }
+
+    #[must_use]
+    pub fn exclude_carriers(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "carrier codes must not be empty");
+        self.term(Selector::carriers(codes).exclude())
+    }
+
+    /// Append an unvalidated fragment. Modifiers cannot be applied to it.
+    #[must_use]
+    pub fn raw(mut self, code: &str) -> Self {
+        self.items.push(RoutingItem::Raw(code.to_string()));
+        self
+    }
+
+    /// Check the code by parsing it back, catching malformed builder
+    /// arguments and `raw()` fragments. Error positions refer to
+    /// [`RoutingCode::build`]'s output.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        parse::parse_routing(&self.to_string()).map(drop)
+    }
+
+    #[must_use]
+    pub fn build(self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for RoutingCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RoutingCode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// An extension code as a sequence of typed commands.
+///
+/// As with [`RoutingCode`], string arguments to the builder methods are taken
+/// as given (empty ones are caught by `debug_assert!` only); call
+/// [`ExtensionCode::validate`] to check the finished code.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionCode {
+    commands: Vec<ExtensionCommand>,
+}
+
+impl ExtensionCode {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse and validate an extension code, rejecting unknown commands and
+    /// malformed arguments.
+    pub fn parse(code: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            commands: parse::parse_extension(code)?,
+        })
+    }
+
+    pub fn commands(&self) -> &[ExtensionCommand] {
+        &self.commands
+    }
+
+    #[must_use]
+    pub fn command(mut self, command: ExtensionCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    #[must_use]
+    pub fn no_codeshare(self) -> Self {
+        self.command(ExtensionCommand::NoCodeshare)
+    }
+
+    #[must_use]
+    pub fn max_stops(self, n: u32) -> Self {
+        self.command(ExtensionCommand::MaxStops(n))
+    }
+
+    #[must_use]
+    pub fn max_duration(self, span: TimeSpan) -> Self {
+        self.command(ExtensionCommand::MaxDuration(span))
+    }
+
+    #[must_use]
+    pub fn max_miles(self, miles: u32) -> Self {
+        self.command(ExtensionCommand::MaxMiles(miles))
+    }
+
+    #[must_use]
+    pub fn min_miles(self, miles: u32) -> Self {
+        self.command(ExtensionCommand::MinMiles(miles))
+    }
+
+    #[must_use]
+    pub fn min_connection(self, span: TimeSpan) -> Self {
+        self.command(ExtensionCommand::MinConnection(span))
+    }
+
+    #[must_use]
+    pub fn max_connection(self, span: TimeSpan) -> Self {
+        self.command(ExtensionCommand::MaxConnection(span))
+    }
+
+    #[must_use]
+    pub fn alliance(self, alliance: Alliance) -> Self {
+        self.command(ExtensionCommand::Alliances(vec![alliance]))
+    }
+
+    #[must_use]
+    pub fn alliances(self, alliances: &[Alliance]) -> Self {
+        debug_assert!(!alliances.is_empty(), "alliances must not be empty");
+        self.command(ExtensionCommand::Alliances(alliances.to_vec()))
+    }
+
+    #[must_use]
+    pub fn airlines(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "airline codes must not be empty");
+        self.command(ExtensionCommand::Airlines(to_strings(codes)))
+    }
+
+    #[must_use]
+    pub fn exclude_airlines(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "airline codes must not be empty");
+        self.command(ExtensionCommand::ExcludeAirlines(to_strings(codes)))
+    }
+
+    #[must_use]
+    pub fn operating_airlines(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "airline codes must not be empty");
+        self.command(ExtensionCommand::OperatingAirlines(to_strings(codes)))
+    }
+
+    #[must_use]
+    pub fn exclude_operating_airlines(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "airline codes must not be empty");
+        self.command(ExtensionCommand::ExcludeOperatingAirlines(to_strings(
+            codes,
+        )))
+    }
+
+    #[must_use]
+    pub fn exclude_cities(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "city codes must not be empty");
+        self.command(ExtensionCommand::ExcludeCities(to_strings(codes)))
+    }
+
+    #[must_use]
+    pub fn no_redeyes(self) -> Self {
+        self.command(ExtensionCommand::NoRedeyes)
+    }
+
+    #[must_use]
+    pub fn no_overnights(self) -> Self {
+        self.command(ExtensionCommand::NoOvernights)
+    }
+
+    #[must_use]
+    pub fn aircraft_type(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "aircraft code must not be empty");
+        self.command(ExtensionCommand::Aircraft(vec![AircraftSpec::Type(
+            code.to_string(),
+        )]))
+    }
+
+    #[must_use]
+    pub fn aircraft_types(self, codes: &[&str]) -> Self {
+        debug_assert!(!codes.is_empty(), "aircraft codes must not be empty");
+        let specs = codes
+            .iter()
+            .map(|c| AircraftSpec::Type(c.to_string()))
+            .collect();
+        self.command(ExtensionCommand::Aircraft(specs))
+    }
+
+    #[must_use]
+    pub fn aircraft_category(self, category: AircraftCategory) -> Self {
+        self.command(ExtensionCommand::Aircraft(vec![AircraftSpec::Category(
+            category,
+        )]))
+    }
+
+    #[must_use]
+    pub fn exclude_aircraft_type(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "aircraft code must not be empty");
+        self.command(ExtensionCommand::ExcludeAircraft(vec![AircraftSpec::Type(
+            code.to_string(),
+        )]))
+    }
+
+    #[must_use]
+    pub fn no_props(self) -> Self {
+        self.command(ExtensionCommand::NoProps)
+    }
+
+    #[must_use]
+    pub fn require_first_class(self) -> Self {
+        self.command(ExtensionCommand::NoFirstClass)
+    }
+
+    #[must_use]
+    pub fn require_cabin(self, cabin: CabinClass) -> Self {
+        self.command(ExtensionCommand::RequireCabin(cabin))
+    }
+
+    #[must_use]
+    pub fn exclude_cabin(self, cabin: CabinClass) -> Self {
+        self.command(ExtensionCommand::ExcludeCabin(cabin))
+    }
+
+    #[must_use]
+    pub fn booking_class(self, code: &str) -> Self {
+        debug_assert!(!code.is_empty(), "booking class must not be empty");
+        self.command(ExtensionCommand::BookingClass(code.to_string()))
+    }
+
+    #[must_use]
+    pub fn fare_basis(self, basis: &str) -> Self {
+        debug_assert!(!basis.is_empty(), "fare basis must not be empty");
+        self.command(ExtensionCommand::FareBasis(basis.to_string()))
+    }
+
+    #[must_use]
+    pub fn raw(self, code: &str) -> Self {
+        self.command(ExtensionCommand::Raw(code.to_string()))
+    }
+
+    /// Check the code by parsing it back, catching malformed builder
+    /// arguments and `raw()` commands. Error positions refer to
+    /// [`ExtensionCode::build`]'s output.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        parse::parse_extension(&self.to_string()).map(drop)
+    }
+
+    #[must_use]
+    pub fn build(self) -> String {
+        self.to_string()
+    }
+}
+
+fn to_strings(codes: &[&str]) -> Vec<String> {
+    codes.iter().map(|s| s.to_string()).collect()
+}
+
+impl Display for ExtensionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, command) in self.commands.iter().enumerate() {
+            if i > 0 {
+                f.write_char(';')?;
+            }
+            write!(f, "{command}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ExtensionCode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routing_nonstop() {
+        let code = RoutingCode::new().nonstop().build();
+        assert_eq!(code, "N");
+    }
+
+    #[test]
+    fn routing_carrier_one_or_more() {
+        let code = RoutingCode::new().carrier_one_or_more("AA").build();
+        assert_eq!(code, "C:AA+");
+    }
+
+    #[test]
+    fn routing_exclude_carriers() {
+        let code = RoutingCode::new()
+            .exclude_carriers(&["AA", "UA", "DL"])
+            .build();
+        assert_eq!(code, "~AA,UA,DL");
+    }
+
+    #[test]
+    fn routing_complex() {
+        let code = RoutingCode::new()
+            .carrier_one_or_more("AA")
+            .connection_at("ORD")
+            .carrier_one_or_more("UA")
+            .build();
+        assert_eq!(code, "C:AA+ X:ORD C:UA+");
+    }
+
+    #[test]
+    fn routing_via_chicago_on_aa() {
+        let code = RoutingCode::new()
+            .carrier("AA")
+            .connection_at("ORD")
+            .carrier("AA")
+            .build();
+        assert_eq!(code, "C:AA X:ORD C:AA");
+    }
+
+    fn assert_routing_round_trip(code: RoutingCode) {
+        let built = code.build();
+        let parsed = RoutingCode::parse(&built).unwrap();
+        assert_eq!(parsed.build(), built);
+    }
+
+    #[test]
+    fn routing_round_trip_every_builder() {
+        let codes = [
+            RoutingCode::new().nonstop(),
+            RoutingCode::new().nonstop_on("AA"),
+            RoutingCode::new().carrier("AA"),
+            RoutingCode::new().carrier_one_or_more("AA"),
+            RoutingCode::new().carriers(&["AA", "UA"]),
+            RoutingCode::new().operating_carrier("AA"),
+            RoutingCode::new().connection(),
+            RoutingCode::new().connection_at("ORD"),
+            RoutingCode::new().connections_at(&["ORD", "DFW"]),
+            RoutingCode::new().flight("AA", 123),
+            RoutingCode::new().any_flight(),
+            RoutingCode::new().term(Term::from(Selector::carrier("AA")).optional()),
+            RoutingCode::new().term(Term::from(Selector::carrier("AA")).one_or_more()),
+            RoutingCode::new().term(Term::any_flight().zero_or_more()),
+            RoutingCode::new().term(Selector::carrier("AA").exclude()),
+            RoutingCode::new().exclude_carrier("NK"),
+            RoutingCode::new().exclude_carriers(&["NK", "F9"]),
+            RoutingCode::new().raw("C:DL+"),
+            RoutingCode::new()
+                .carrier_one_or_more("AA")
+                .connection_at("ORD")
+                .carrier_one_or_more("UA"),
+        ];
+        for code in codes {
+            assert_routing_round_trip(code);
+        }
+    }
+
+    #[test]
+    fn routing_parse_typed_terms() {
+        let terms = RoutingCode::parse("C:AA+ X:ORD ~UA,DL F:B61234?")
+            .unwrap()
+            .terms()
+            .unwrap();
+
+        assert_eq!(terms[0].quantifier, Some(Quantifier::OneOrMore));
+        assert_eq!(
+            terms[1].kind,
+            TermKind::Match {
+                negated: false,
+                selector: Selector::ConnectionAt("ORD".to_string()),
+            }
+        );
+        assert_eq!(
+            terms[2].kind,
+            TermKind::Match {
+                negated: true,
+                selector: Selector::Carriers(vec!["UA".to_string(), "DL".to_string()]),
+            }
+        );
+        assert_eq!(
+            terms[3].kind,
+            TermKind::Match {
+                negated: false,
+                selector: Selector::Flight {
+                    carrier: "B6".to_string(),
+                    number: 1234,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn routing_parse_normalizes() {
+        let code: RoutingCode = "  c:aa+   x:ord\tn ".parse().unwrap();
+        assert_eq!(code.to_string(), "C:AA+ X:ORD N");
+    }
+
+    #[test]
+    fn routing_parse_errors_have_positions() {
+        let err = RoutingCode::parse("C:AA X:ORDX").unwrap_err();
+        assert_eq!(err.position, 7);
+        assert_eq!(err.kind, ParseErrorKind::InvalidAirport("ORDX".to_string()));
+
+        let err = RoutingCode::parse("N C:AA+?").unwrap_err();
+        assert_eq!(err.position, 6);
+        assert_eq!(err.kind, ParseErrorKind::StackedModifiers);
+
+        let err = RoutingCode::parse("F ~X").unwrap_err();
+        assert_eq!(err.position, 2);
+        assert_eq!(err.kind, ParseErrorKind::NotExcludable("X".to_string()));
+
+        let err = RoutingCode::parse("AA,ORD").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::MixedCodes(_)));
+
+        let err = RoutingCode::parse("Z:AA").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnknownTerm("Z:AA".to_string()));
+    }
+
+    #[test]
+    fn routing_typed_terms_build() {
+        let code = RoutingCode::new()
+            .term(Term::from(Selector::carrier("AA")).one_or_more())
+            .term(Selector::airports(&["ORD", "DFW"]).exclude())
+            .term(Term::any_flight().optional());
+        assert_eq!(code.build(), "C:AA+ ~ORD,DFW F?");
+    }
+
+    #[test]
+    fn routing_quantifier_replaces_rather_than_stacks() {
+        let term = Term::any_flight().optional().one_or_more();
+        assert_eq!(term.to_string(), "F+");
+    }
+
+    #[test]
+    fn routing_validate_checks_builder_arguments() {
+        assert!(RoutingCode::new().carrier("AA").validate().is_ok());
+
+        let err = RoutingCode::new()
+            .carrier("AA")
+            .connection_at("CHICAGO")
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.position, 7);
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InvalidAirport("CHICAGO".to_string())
+        );
+
+        let err = RoutingCode::new().raw("C:AA+?").validate().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::StackedModifiers);
+    }
+
+    #[test]
+    fn extension_basic() {
+        let code = ExtensionCode::new().no_codeshare().max_stops(2).build();
+        assert_eq!(code, "-CODESHARE;MAXSTOPS 2");
+    }
+
+    #[test]
+    fn extension_duration() {
+        let code = ExtensionCode::new()
+            .max_duration(TimeSpan::new(6, 45).unwrap())
+            .build();
+        assert_eq!(code, "MAXDUR 6:45");
+    }
+
+    #[test]
+    fn extension_alliance() {
+        let code = ExtensionCode::new()
+            .alliance(Alliance::StarAlliance)
+            .build();
+        assert_eq!(code, "ALLIANCE star-alliance");
+    }
+
+    #[test]
+    fn extension_complex() {
+        let code = ExtensionCode::new()
+            .alliance(Alliance::OneWorld)
+            .no_redeyes()
+            .max_duration(TimeSpan::new(8, 0).unwrap())
+            .no_props()
+            .build();
+        assert_eq!(code, "ALLIANCE oneworld;-REDEYES;MAXDUR 8:00;-PROPS");
+    }
+
+    #[test]
+    fn extension_aircraft() {
+        let code = ExtensionCode::new()
+            .aircraft_types(&[aircraft::BOEING_787, aircraft::AIRBUS_A350])
+            .build();
+        assert_eq!(code, "AIRCRAFT T:787 T:350");
+    }
+
+    fn assert_extension_round_trip(code: ExtensionCode) {
+        let built = code.build();
+        let parsed = ExtensionCode::parse(&built).unwrap();
+        assert_eq!(parsed.build(), built);
+    }
+
+    #[test]
+    fn extension_round_trip_every_builder() {
+        let codes = [
+            ExtensionCode::new().no_codeshare(),
+            ExtensionCode::new().max_stops(1),
+            ExtensionCode::new().max_duration(TimeSpan::new(12, 5).unwrap()),
+            ExtensionCode::new().max_miles(3000),
+            ExtensionCode::new().min_miles(500),
+            ExtensionCode::new().min_connection(TimeSpan::new(0, 45).unwrap()),
+            ExtensionCode::new().max_connection(TimeSpan::new(4, 0).unwrap()),
+            ExtensionCode::new().alliance(Alliance::SkyTeam),
+            ExtensionCode::new().alliances(&[Alliance::OneWorld, Alliance::StarAlliance]),
+            ExtensionCode::new().airlines(&["AA", "B6"]),
+            ExtensionCode::new().exclude_airlines(&["NK"]),
+            ExtensionCode::new().operating_airlines(&["DL"]),
+            ExtensionCode::new().exclude_operating_airlines(&["OO", "9E"]),
+            ExtensionCode::new().exclude_cities(&["ORD", "EWR"]),
+            ExtensionCode::new().no_redeyes(),
+            ExtensionCode::new().no_overnights(),
+            ExtensionCode::new().aircraft_type(aircraft::BOEING_777),
+            ExtensionCode::new().aircraft_types(&[aircraft::AIRBUS_A380, aircraft::EMBRAER_E175]),
+            ExtensionCode::new().aircraft_category(AircraftCategory::Turboprop),
+            ExtensionCode::new().exclude_aircraft_type(aircraft::BOMBARDIER_CRJ),
+            ExtensionCode::new().no_props(),
+            ExtensionCode::new().require_first_class(),
+            ExtensionCode::new().require_cabin(CabinClass::PremiumEconomy),
+            ExtensionCode::new().exclude_cabin(CabinClass::Economy),
+            ExtensionCode::new().booking_class("J"),
+            ExtensionCode::new().fare_basis("YOW"),
+            ExtensionCode::new()
+                .alliance(Alliance::OneWorld)
+                .no_redeyes()
+                .max_duration(TimeSpan::new(8, 0).unwrap())
+                .no_props(),
+        ];
+        for code in codes {
+            assert_extension_round_trip(code);
+        }
+    }
+
+    #[test]
+    fn extension_parse_typed_commands() {
+        let code = ExtensionCode::parse("maxstops 1; -redeyes ;AIRCRAFT T:787 C:JET").unwrap();
+        assert_eq!(
+            code.commands(),
+            [
+                ExtensionCommand::MaxStops(1),
+                ExtensionCommand::NoRedeyes,
+                ExtensionCommand::Aircraft(vec![
+                    AircraftSpec::Type("787".to_string()),
+                    AircraftSpec::Category(AircraftCategory::Jet),
+                ]),
+            ]
+        );
+        assert_eq!(code.to_string(), "MAXSTOPS 1;-REDEYES;AIRCRAFT T:787 C:JET");
+    }
+
+    #[test]
+    fn extension_parse_rejects_bad_input() {
+        let err = ExtensionCode::parse("MAXDUR 6:75").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MinutesOutOfRange(75));
+        assert_eq!(err.position, 9);
+
+        let err = ExtensionCode::parse("-REDEYES;FOO 1").unwrap_err();
+        assert_eq!(err.position, 9);
+        assert_eq!(err.kind, ParseErrorKind::UnknownCommand("FOO".to_string()));
+
+        let err = ExtensionCode::parse("MAXSTOPS").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::MissingArgument("MAXSTOPS".to_string())
+        );
+
+        let err = ExtensionCode::parse("MAXSTOPS two").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidNumber("TWO".to_string()));
+
+        let err = ExtensionCode::parse("ALLIANCE vanilla").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::InvalidAlliance(_)));
+
+        let err = ExtensionCode::parse("-PROPS now").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::UnexpectedArgument(_)));
+
+        let err = ExtensionCode::parse("+CABIN 4").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::InvalidCabin(_)));
+    }
+
+    #[test]
+    fn extension_validate_checks_builder_arguments() {
+        assert!(ExtensionCode::new().airlines(&["AA"]).validate().is_ok());
+
+        let err = ExtensionCode::new()
+            .no_redeyes()
+            .raw("FOO 1")
+            .validate()
+            .unwrap_err();
+        assert_eq!(err.position, 9);
+        assert_eq!(err.kind, ParseErrorKind::UnknownCommand("FOO".to_string()));
+    }
+
+    #[test]
+    fn time_span_rejects_out_of_range_values() {
+        assert_eq!(TimeSpan::new(6, 59).map(TimeSpan::total_minutes), Some(419));
+        assert_eq!(TimeSpan::new(6, 60), None);
+        assert_eq!(TimeSpan::new(u32::MAX / 60 + 1, 0), None);
+
+        let err = ExtensionCode::parse("MAXDUR 99999999:00").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InvalidDuration("99999999:00".to_string())
+        );
+    }
+
+    #[test]
+    fn extension_cabin() {
+        let code = ExtensionCode::new()
+            .require_cabin(CabinClass::Business)
+            .build();
+        assert_eq!(code, "+CABIN 2");
+    }
+}
@@ -0,0 +1,404 @@
+use thiserror::Error;
+
+use super::command::{AircraftSpec, ExtensionCommand, TimeSpan};
+use super::term::{Quantifier, Selector, Term, TermKind};
+use super::{AircraftCategory, Alliance, CabinClass};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{kind} at offset {position}")]
+pub struct ParseError {
+    /// Byte offset into the code being parsed.
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseErrorKind {
+    #[error("invalid carrier code {0:?}")]
+    InvalidCarrier(String),
+
+    #[error("invalid airport code {0:?}")]
+    InvalidAirport(String),
+
+    #[error("invalid flight {0:?}")]
+    InvalidFlight(String),
+
+    #[error("unknown term {0:?}")]
+    UnknownTerm(String),
+
+    #[error("{0:?} cannot be excluded")]
+    NotExcludable(String),
+
+    #[error("more than one repetition modifier")]
+    StackedModifiers,
+
+    #[error("mixed carrier and airport codes in {0:?}")]
+    MixedCodes(String),
+
+    #[error("unknown command {0:?}")]
+    UnknownCommand(String),
+
+    #[error("{0} requires an argument")]
+    MissingArgument(String),
+
+    #[error("unexpected argument {0:?}")]
+    UnexpectedArgument(String),
+
+    #[error("invalid number {0:?}")]
+    InvalidNumber(String),
+
+    #[error("invalid duration {0:?}, expected h:mm")]
+    InvalidDuration(String),
+
+    #[error("minutes must be < 60, got {0}")]
+    MinutesOutOfRange(u32),
+
+    #[error("invalid alliance {0:?}")]
+    InvalidAlliance(String),
+
+    #[error("invalid cabin {0:?}")]
+    InvalidCabin(String),
+
+    #[error("invalid aircraft {0:?}")]
+    InvalidAircraft(String),
+
+    #[error("invalid booking class {0:?}")]
+    InvalidBookingClass(String),
+
+    #[error("invalid fare basis {0:?}")]
+    InvalidFareBasis(String),
+}
+
+/// Split `input` on whitespace, keeping each word's byte offset.
+pub(super) fn words(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.split_whitespace().map(move |w| {
+        let offset = w.as_ptr() as usize - input.as_ptr() as usize;
+        (offset, w)
+    })
+}
+
+pub(super) fn is_carrier_code(code: &str) -> bool {
+    code.len() == 2
+        && code
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && code.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+pub(super) fn is_airport_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+fn err(position: usize, kind: ParseErrorKind) -> ParseError {
+    ParseError { position, kind }
+}
+
+pub(super) fn carrier_at(code: &str, position: usize) -> Result<String, ParseError> {
+    if is_carrier_code(code) {
+        Ok(code.to_string())
+    } else {
+        Err(err(
+            position,
+            ParseErrorKind::InvalidCarrier(code.to_string()),
+        ))
+    }
+}
+
+pub(super) fn airport_at(code: &str, position: usize) -> Result<String, ParseError> {
+    if is_airport_code(code) {
+        Ok(code.to_string())
+    } else {
+        Err(err(
+            position,
+            ParseErrorKind::InvalidAirport(code.to_string()),
+        ))
+    }
+}
+
+/// Parse a routing code into its terms. Codes are case-insensitive and
+/// normalized to upper case.
+pub fn parse_routing(input: &str) -> Result<Vec<Term>, ParseError> {
+    words(input)
+        .map(|(offset, word)| parse_term(&word.to_ascii_uppercase(), offset))
+        .collect()
+}
+
+fn parse_term(word: &str, offset: usize) -> Result<Term, ParseError> {
+    let (negated, body, body_offset) = match word.strip_prefix('~') {
+        Some(rest) => (true, rest, offset + 1),
+        None => (false, word, offset),
+    };
+
+    let mut body = body;
+    let mut quantifier = None;
+    while let Some(q) = body.chars().last().and_then(Quantifier::from_char) {
+        if quantifier.is_some() {
+            return Err(err(
+                body_offset + body.len() - 1,
+                ParseErrorKind::StackedModifiers,
+            ));
+        }
+        quantifier = Some(q);
+        body = &body[..body.len() - 1];
+    }
+
+    let kind = match body.split_once(':') {
+        None => match body {
+            "N" => TermKind::Nonstop(None),
+            "X" => TermKind::AnyConnection,
+            "F" => TermKind::AnyFlight,
+            _ => TermKind::Match {
+                negated,
+                selector: parse_code_list(body, body_offset)?,
+            },
+        },
+        Some((prefix, arg)) => {
+            let arg_offset = body_offset + prefix.len() + 1;
+            match prefix {
+                "N" => TermKind::Nonstop(Some(carrier_at(arg, arg_offset)?)),
+                "C" => TermKind::Match {
+                    negated,
+                    selector: Selector::Carrier(carrier_at(arg, arg_offset)?),
+                },
+                "O" => TermKind::Match {
+                    negated,
+                    selector: Selector::OperatingCarrier(carrier_at(arg, arg_offset)?),
+                },
+                "X" => TermKind::Match {
+                    negated,
+                    selector: Selector::ConnectionAt(airport_at(arg, arg_offset)?),
+                },
+                "F" => TermKind::Match {
+                    negated,
+                    selector: parse_flight(arg, arg_offset)?,
+                },
+                _ => {
+                    return Err(err(
+                        body_offset,
+                        ParseErrorKind::UnknownTerm(body.to_string()),
+                    ));
+                }
+            }
+        }
+    };
+
+    if negated && !matches!(kind, TermKind::Match { .. }) {
+        return Err(err(offset, ParseErrorKind::NotExcludable(body.to_string())));
+    }
+
+    Ok(Term { kind, quantifier })
+}
+
+fn parse_flight(arg: &str, offset: usize) -> Result<Selector, ParseError> {
+    let invalid = || err(offset, ParseErrorKind::InvalidFlight(arg.to_string()));
+    if arg.len() < 3 || !arg.is_char_boundary(2) {
+        return Err(invalid());
+    }
+    let (carrier, number) = arg.split_at(2);
+    if !is_carrier_code(carrier) || number.len() > 4 {
+        return Err(invalid());
+    }
+    let number = number.parse().map_err(|_| invalid())?;
+    Ok(Selector::Flight {
+        carrier: carrier.to_string(),
+        number,
+    })
+}
+
+fn parse_code_list(body: &str, offset: usize) -> Result<Selector, ParseError> {
+    let mut carriers = Vec::new();
+    let mut airports = Vec::new();
+    let mut pos = offset;
+
+    for code in body.split(',') {
+        if is_carrier_code(code) {
+            carriers.push(code.to_string());
+        } else if is_airport_code(code) {
+            airports.push(code.to_string());
+        } else {
+            return Err(err(pos, ParseErrorKind::UnknownTerm(code.to_string())));
+        }
+        pos += code.len() + 1;
+    }
+
+    match (carriers.is_empty(), airports.is_empty()) {
+        (false, true) => Ok(Selector::Carriers(carriers)),
+        (true, false) => Ok(Selector::Airports(airports)),
+        _ => Err(err(offset, ParseErrorKind::MixedCodes(body.to_string()))),
+    }
+}
+
+/// Parse an extension code (`;`-separated commands). Keywords and codes are
+/// case-insensitive; empty commands are ignored.
+pub fn parse_extension(input: &str) -> Result<Vec<ExtensionCommand>, ParseError> {
+    let mut commands = Vec::new();
+    let mut offset = 0;
+
+    for segment in input.split(';') {
+        let start = offset;
+        offset += segment.len() + 1;
+
+        let upper = segment.to_ascii_uppercase();
+        let mut words = words(&upper).map(|(o, w)| (start + o, w));
+        let Some((kw_offset, keyword)) = words.next() else {
+            continue;
+        };
+        let args: Vec<(usize, &str)> = words.collect();
+        let end = start + segment.trim_end().len();
+        commands.push(parse_command(keyword, kw_offset, &args, end)?);
+    }
+
+    Ok(commands)
+}
+
+fn parse_command(
+    keyword: &str,
+    offset: usize,
+    args: &[(usize, &str)],
+    end: usize,
+) -> Result<ExtensionCommand, ParseError> {
+    use ExtensionCommand as C;
+
+    let command = match keyword {
+        "-CODESHARE" => no_args(C::NoCodeshare, args)?,
+        "-REDEYES" => no_args(C::NoRedeyes, args)?,
+        "-OVERNIGHTS" => no_args(C::NoOvernights, args)?,
+        "-PROPS" => no_args(C::NoProps, args)?,
+        "-NOFIRSTCLASS" => no_args(C::NoFirstClass, args)?,
+        "MAXSTOPS" => C::MaxStops(number(one_arg(keyword, args, end)?)?),
+        "MAXMILES" => C::MaxMiles(number(one_arg(keyword, args, end)?)?),
+        "MINMILES" => C::MinMiles(number(one_arg(keyword, args, end)?)?),
+        "MAXDUR" => C::MaxDuration(time_span(one_arg(keyword, args, end)?)?),
+        "MINCONNECT" => C::MinConnection(time_span(one_arg(keyword, args, end)?)?),
+        "MAXCONNECT" => C::MaxConnection(time_span(one_arg(keyword, args, end)?)?),
+        "ALLIANCE" => C::Alliances(many(keyword, args, end, |(o, w)| {
+            Alliance::from_code(w)
+                .ok_or_else(|| err(o, ParseErrorKind::InvalidAlliance(w.to_string())))
+        })?),
+        "AIRLINES" => C::Airlines(many(keyword, args, end, |(o, w)| carrier_at(w, o))?),
+        "-AIRLINES" => C::ExcludeAirlines(many(keyword, args, end, |(o, w)| carrier_at(w, o))?),
+        "OPAIRLINES" => C::OperatingAirlines(many(keyword, args, end, |(o, w)| carrier_at(w, o))?),
+        "-OPAIRLINES" => {
+            C::ExcludeOperatingAirlines(many(keyword, args, end, |(o, w)| carrier_at(w, o))?)
+        }
+        "-CITIES" => C::ExcludeCities(many(keyword, args, end, |(o, w)| airport_at(w, o))?),
+        "AIRCRAFT" => C::Aircraft(many(keyword, args, end, aircraft_spec)?),
+        "-AIRCRAFT" => C::ExcludeAircraft(many(keyword, args, end, aircraft_spec)?),
+        "+CABIN" => C::RequireCabin(cabin(one_arg(keyword, args, end)?)?),
+        "-CABIN" => C::ExcludeCabin(cabin(one_arg(keyword, args, end)?)?),
+        "F" => fare_restriction(one_arg(keyword, args, end)?)?,
+        _ => {
+            return Err(err(
+                offset,
+                ParseErrorKind::UnknownCommand(keyword.to_string()),
+            ));
+        }
+    };
+    Ok(command)
+}
+
+fn no_args(
+    command: ExtensionCommand,
+    args: &[(usize, &str)],
+) -> Result<ExtensionCommand, ParseError> {
+    match args.first() {
+        Some(&(o, w)) => Err(err(o, ParseErrorKind::UnexpectedArgument(w.to_string()))),
+        None => Ok(command),
+    }
+}
+
+fn one_arg<'a>(
+    keyword: &str,
+    args: &[(usize, &'a str)],
+    end: usize,
+) -> Result<(usize, &'a str), ParseError> {
+    match args {
+        [] => Err(err(
+            end,
+            ParseErrorKind::MissingArgument(keyword.to_string()),
+        )),
+        [arg] => Ok(*arg),
+        [_, (o, w), ..] => Err(err(*o, ParseErrorKind::UnexpectedArgument(w.to_string()))),
+    }
+}
+
+fn many<T>(
+    keyword: &str,
+    args: &[(usize, &str)],
+    end: usize,
+    f: impl Fn((usize, &str)) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    if args.is_empty() {
+        return Err(err(
+            end,
+            ParseErrorKind::MissingArgument(keyword.to_string()),
+        ));
+    }
+    args.iter().map(|&arg| f(arg)).collect()
+}
+
+fn number((offset, word): (usize, &str)) -> Result<u32, ParseError> {
+    word.parse()
+        .map_err(|_| err(offset, ParseErrorKind::InvalidNumber(word.to_string())))
+}
+
+fn time_span((offset, word): (usize, &str)) -> Result<TimeSpan, ParseError> {
+    let invalid = || err(offset, ParseErrorKind::InvalidDuration(word.to_string()));
+    let (hours, minutes) = word.split_once(':').ok_or_else(invalid)?;
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(hours) || !all_digits(minutes) || minutes.len() > 2 {
+        return Err(invalid());
+    }
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 {
+        return Err(err(
+            offset + word.len() - 2,
+            ParseErrorKind::MinutesOutOfRange(minutes),
+        ));
+    }
+    TimeSpan::new(hours, minutes).ok_or_else(invalid)
+}
+
+fn cabin((offset, word): (usize, &str)) -> Result<CabinClass, ParseError> {
+    CabinClass::from_code(word)
+        .ok_or_else(|| err(offset, ParseErrorKind::InvalidCabin(word.to_string())))
+}
+
+fn aircraft_spec((offset, word): (usize, &str)) -> Result<AircraftSpec, ParseError> {
+    let invalid = || err(offset, ParseErrorKind::InvalidAircraft(word.to_string()));
+    match word.split_once(':') {
+        Some(("T", code)) if code.len() == 3 && code.bytes().all(|b| b.is_ascii_alphanumeric()) => {
+            Ok(AircraftSpec::Type(code.to_string()))
+        }
+        Some(("C", category)) => AircraftCategory::from_code(category)
+            .map(AircraftSpec::Category)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+fn fare_restriction((offset, word): (usize, &str)) -> Result<ExtensionCommand, ParseError> {
+    if let Some(code) = word.strip_prefix("BC=") {
+        if code.len() == 1 && code.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Ok(ExtensionCommand::BookingClass(code.to_string()));
+        }
+        return Err(err(
+            offset + 3,
+            ParseErrorKind::InvalidBookingClass(code.to_string()),
+        ));
+    }
+    if let Some(basis) = word.strip_prefix("..") {
+        if !basis.is_empty() && basis.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Ok(ExtensionCommand::FareBasis(basis.to_string()));
+        }
+        return Err(err(
+            offset + 2,
+            ParseErrorKind::InvalidFareBasis(basis.to_string()),
+        ));
+    }
+    Err(err(
+        offset,
+        ParseErrorKind::UnexpectedArgument(word.to_string()),
+    ))
+}
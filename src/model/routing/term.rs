@@ -0,0 +1,221 @@
+use std::fmt::{self, Display, Write};
+
+/// Repetition suffix on a routing term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantifier {
+    /// `?`: zero or one.
+    Optional,
+    /// `+`: one or more.
+    OneOrMore,
+    /// `*`: zero or more.
+    ZeroOrMore,
+}
+
+impl Quantifier {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '?' => Some(Self::Optional),
+            '+' => Some(Self::OneOrMore),
+            '*' => Some(Self::ZeroOrMore),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Optional => '?',
+            Self::OneOrMore => '+',
+            Self::ZeroOrMore => '*',
+        }
+    }
+
+    /// Minimum and maximum repetitions (`None` = unbounded).
+    pub fn bounds(self) -> (usize, Option<usize>) {
+        match self {
+            Self::Optional => (0, Some(1)),
+            Self::OneOrMore => (1, None),
+            Self::ZeroOrMore => (0, None),
+        }
+    }
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char(self.as_char())
+    }
+}
+
+/// A term that names specific carriers, airports or flights, and so can be
+/// excluded with `~`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// `C:AA`: a flight marketed by the carrier.
+    Carrier(String),
+    /// `O:AA`: a flight operated by the carrier.
+    OperatingCarrier(String),
+    /// `X:ORD`: a connection at the airport.
+    ConnectionAt(String),
+    /// `F:AA123`: a specific flight.
+    Flight { carrier: String, number: u32 },
+    /// `AA,UA`: a flight on any of the carriers.
+    Carriers(Vec<String>),
+    /// `ORD,DFW`: a connection at any of the airports.
+    Airports(Vec<String>),
+}
+
+impl Selector {
+    pub fn carrier(code: &str) -> Self {
+        Self::Carrier(code.to_string())
+    }
+
+    pub fn operating_carrier(code: &str) -> Self {
+        Self::OperatingCarrier(code.to_string())
+    }
+
+    pub fn connection_at(airport: &str) -> Self {
+        Self::ConnectionAt(airport.to_string())
+    }
+
+    pub fn flight(carrier: &str, number: u32) -> Self {
+        Self::Flight {
+            carrier: carrier.to_string(),
+            number,
+        }
+    }
+
+    pub fn carriers(codes: &[&str]) -> Self {
+        Self::Carriers(codes.iter().map(|c| c.to_string()).collect())
+    }
+
+    pub fn airports(codes: &[&str]) -> Self {
+        Self::Airports(codes.iter().map(|c| c.to_string()).collect())
+    }
+
+    /// The `~`-prefixed term matching anything but this selector.
+    pub fn exclude(self) -> Term {
+        Term::new(TermKind::Match {
+            negated: true,
+            selector: self,
+        })
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Carrier(code) => write!(f, "C:{code}"),
+            Self::OperatingCarrier(code) => write!(f, "O:{code}"),
+            Self::ConnectionAt(code) => write!(f, "X:{code}"),
+            Self::Flight { carrier, number } => write!(f, "F:{carrier}{number}"),
+            Self::Carriers(codes) | Self::Airports(codes) => f.write_str(&codes.join(",")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermKind {
+    /// `N` or `N:AA`: a nonstop flight, optionally on one carrier.
+    Nonstop(Option<String>),
+    /// `X`: any connection.
+    AnyConnection,
+    /// `F`: any flight.
+    AnyFlight,
+    /// A selector, `~`-prefixed when `negated`.
+    Match { negated: bool, selector: Selector },
+}
+
+/// One space-separated term of a routing code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub kind: TermKind,
+    pub quantifier: Option<Quantifier>,
+}
+
+impl Term {
+    pub fn new(kind: TermKind) -> Self {
+        Self {
+            kind,
+            quantifier: None,
+        }
+    }
+
+    pub fn nonstop() -> Self {
+        Self::new(TermKind::Nonstop(None))
+    }
+
+    pub fn nonstop_on(carrier: &str) -> Self {
+        Self::new(TermKind::Nonstop(Some(carrier.to_string())))
+    }
+
+    pub fn any_connection() -> Self {
+        Self::new(TermKind::AnyConnection)
+    }
+
+    pub fn any_flight() -> Self {
+        Self::new(TermKind::AnyFlight)
+    }
+
+    /// Set the repetition modifier. A term holds at most one, so this
+    /// replaces any earlier one.
+    #[must_use]
+    pub fn with_quantifier(mut self, quantifier: Quantifier) -> Self {
+        self.quantifier = Some(quantifier);
+        self
+    }
+
+    #[must_use]
+    pub fn optional(self) -> Self {
+        self.with_quantifier(Quantifier::Optional)
+    }
+
+    #[must_use]
+    pub fn one_or_more(self) -> Self {
+        self.with_quantifier(Quantifier::OneOrMore)
+    }
+
+    #[must_use]
+    pub fn zero_or_more(self) -> Self {
+        self.with_quantifier(Quantifier::ZeroOrMore)
+    }
+
+    /// Whether the term matches a flight (as opposed to a connection point).
+    pub fn is_flight(&self) -> bool {
+        match &self.kind {
+            TermKind::Nonstop(_) | TermKind::AnyFlight => true,
+            TermKind::AnyConnection => false,
+            TermKind::Match { selector, .. } => {
+                !matches!(selector, Selector::ConnectionAt(_) | Selector::Airports(_))
+            }
+        }
+    }
+}
+
+impl From<Selector> for Term {
+    fn from(selector: Selector) -> Self {
+        Self::new(TermKind::Match {
+            negated: false,
+            selector,
+        })
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TermKind::Nonstop(None) => f.write_char('N')?,
+            TermKind::Nonstop(Some(carrier)) => write!(f, "N:{carrier}")?,
+            TermKind::AnyConnection => f.write_char('X')?,
+            TermKind::AnyFlight => f.write_char('F')?,
+            TermKind::Match { negated, selector } => {
+                if *negated {
+                    f.write_char('~')?;
+                }
+                write!(f, "{selector}")?;
+            }
+        }
+        if let Some(q) = self.quantifier {
+            write!(f, "{q}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::money::Money;
+
+/// Typed `fareRules` summarizer output, as requested by
+/// [`SummarizeRequest::fare_rules`](super::summarize::SummarizeRequest::fare_rules).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FareRules {
+    #[serde(default, alias = "fareRules")]
+    pub fares: Vec<FareRuleSet>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// Rules text for one fare component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FareRuleSet {
+    #[serde(default)]
+    pub carrier: Option<String>,
+    #[serde(default, rename = "code", alias = "fareBasis")]
+    pub basis: Option<String>,
+    #[serde(default, rename = "originCity")]
+    pub origin: Option<String>,
+    #[serde(default, rename = "destinationCity")]
+    pub destination: Option<String>,
+    #[serde(default, alias = "categories")]
+    pub rules: Vec<RuleSection>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSection {
+    #[serde(default, alias = "number")]
+    pub category: Option<u32>,
+    #[serde(default, alias = "name")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "text_or_lines")]
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// ATPCO rule categories that show up in ITA's rules display.
+pub mod category {
+    pub const ELIGIBILITY: u32 = 1;
+    pub const DAY_TIME: u32 = 2;
+    pub const SEASONALITY: u32 = 3;
+    pub const FLIGHT_APPLICATION: u32 = 4;
+    pub const ADVANCE_PURCHASE: u32 = 5;
+    pub const MINIMUM_STAY: u32 = 6;
+    pub const MAXIMUM_STAY: u32 = 7;
+    pub const STOPOVERS: u32 = 8;
+    pub const TRANSFERS: u32 = 9;
+    pub const COMBINATIONS: u32 = 10;
+    pub const BLACKOUT_DATES: u32 = 11;
+    pub const SURCHARGES: u32 = 12;
+    pub const ACCOMPANIED_TRAVEL: u32 = 13;
+    pub const TRAVEL_RESTRICTIONS: u32 = 14;
+    pub const SALES_RESTRICTIONS: u32 = 15;
+    pub const PENALTIES: u32 = 16;
+    pub const HIGHER_INTERMEDIATE_POINT: u32 = 17;
+    pub const TICKET_ENDORSEMENTS: u32 = 18;
+    pub const CHILDREN_DISCOUNTS: u32 = 19;
+    pub const TOUR_CONDUCTOR_DISCOUNTS: u32 = 20;
+    pub const AGENT_DISCOUNTS: u32 = 21;
+    pub const OTHER_DISCOUNTS: u32 = 22;
+    pub const MISCELLANEOUS: u32 = 23;
+    pub const FARE_BY_RULE: u32 = 25;
+    pub const GROUPS: u32 = 26;
+    pub const TOURS: u32 = 27;
+    pub const VISIT_ANOTHER_COUNTRY: u32 = 28;
+    pub const DEPOSITS: u32 = 29;
+    pub const VOLUNTARY_CHANGES: u32 = 31;
+    pub const VOLUNTARY_REFUNDS: u32 = 33;
+    pub const NEGOTIATED_FARES: u32 = 35;
+    pub const APPLICATION: u32 = 50;
+
+    pub(super) const TITLES: &[(u32, &str)] = &[
+        (ELIGIBILITY, "ELIGIBILITY"),
+        (DAY_TIME, "DAY/TIME"),
+        (SEASONALITY, "SEASONALITY"),
+        (FLIGHT_APPLICATION, "FLIGHT APPLICATION"),
+        (ADVANCE_PURCHASE, "ADVANCE RESERVATIONS/TICKETING"),
+        (MINIMUM_STAY, "MINIMUM STAY"),
+        (MAXIMUM_STAY, "MAXIMUM STAY"),
+        (STOPOVERS, "STOPOVERS"),
+        (TRANSFERS, "TRANSFERS"),
+        (COMBINATIONS, "PERMITTED COMBINATIONS"),
+        (BLACKOUT_DATES, "BLACKOUT DATES"),
+        (SURCHARGES, "SURCHARGES"),
+        (ACCOMPANIED_TRAVEL, "ACCOMPANIED TRAVEL"),
+        (TRAVEL_RESTRICTIONS, "TRAVEL RESTRICTIONS"),
+        (SALES_RESTRICTIONS, "SALES RESTRICTIONS"),
+        (PENALTIES, "PENALTIES"),
+        (HIGHER_INTERMEDIATE_POINT, "HIP/MILEAGE EXCEPTIONS"),
+        (TICKET_ENDORSEMENTS, "TICKET ENDORSEMENTS"),
+        (CHILDREN_DISCOUNTS, "CHILDREN DISCOUNTS"),
+        (TOUR_CONDUCTOR_DISCOUNTS, "TOUR CONDUCTOR DISCOUNTS"),
+        (AGENT_DISCOUNTS, "AGENT DISCOUNTS"),
+        (OTHER_DISCOUNTS, "ALL OTHER DISCOUNTS"),
+        (MISCELLANEOUS, "MISCELLANEOUS PROVISIONS"),
+        (FARE_BY_RULE, "FARE BY RULE"),
+        (GROUPS, "GROUPS"),
+        (TOURS, "TOURS"),
+        (VISIT_ANOTHER_COUNTRY, "VISIT ANOTHER COUNTRY"),
+        (DEPOSITS, "DEPOSITS"),
+        (VOLUNTARY_CHANGES, "VOLUNTARY CHANGES"),
+        (VOLUNTARY_REFUNDS, "VOLUNTARY REFUNDS"),
+        (NEGOTIATED_FARES, "NEGOTIATED FARES"),
+        (APPLICATION, "APPLICATION AND OTHER CONDITIONS"),
+    ];
+
+    /// Standard ATPCO title for a category number.
+    pub fn title(number: u32) -> Option<&'static str> {
+        TITLES.iter().find(|(n, _)| *n == number).map(|(_, t)| *t)
+    }
+}
+
+impl RuleSection {
+    /// Category number, from the explicit field, a `16. PENALTIES` style
+    /// title, or the standard title text.
+    pub fn category(&self) -> Option<u32> {
+        if self.category.is_some() {
+            return self.category;
+        }
+
+        let title = self.title.as_deref()?.trim().to_ascii_uppercase();
+        let digits: String = title.chars().take_while(char::is_ascii_digit).collect();
+        if let Ok(n) = digits.parse() {
+            return Some(n);
+        }
+        category::TITLES
+            .iter()
+            .find(|(_, t)| title.contains(t))
+            .map(|(n, _)| *n)
+    }
+
+    /// The section title, falling back to the standard ATPCO title.
+    pub fn display_title(&self) -> Option<&str> {
+        self.title
+            .as_deref()
+            .or_else(|| self.category().and_then(category::title))
+    }
+}
+
+impl FareRules {
+    pub fn fare(&self, basis: &str) -> Option<&FareRuleSet> {
+        self.fares
+            .iter()
+            .find(|f| f.basis.as_deref() == Some(basis))
+    }
+}
+
+impl FareRuleSet {
+    /// Sections grouped by category number; sections with no recognizable
+    /// category are left out.
+    pub fn by_category(&self) -> BTreeMap<u32, Vec<&RuleSection>> {
+        let mut groups: BTreeMap<u32, Vec<&RuleSection>> = BTreeMap::new();
+        for section in &self.rules {
+            if let Some(n) = section.category() {
+                groups.entry(n).or_default().push(section);
+            }
+        }
+        groups
+    }
+
+    /// Concatenated text of every section in `category`.
+    pub fn category_text(&self, number: u32) -> Option<String> {
+        let texts: Vec<&str> = self
+            .rules
+            .iter()
+            .filter(|s| s.category() == Some(number))
+            .map(|s| s.text.as_str())
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+
+    pub fn facts(&self) -> RuleFacts {
+        RuleFacts::extract(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ChangeFee {
+    Free,
+    Amount(Money),
+    NotPermitted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Stay {
+    None,
+    SaturdayNight,
+    Days(u32),
+    Months(u32),
+}
+
+/// Key facts read out of the rules text.
+///
+/// Rules are free text written by each carrier; extraction recognizes the
+/// common ATPCO phrasings and leaves a field `None` when the text does not
+/// say clearly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleFacts {
+    pub change_fee: Option<ChangeFee>,
+    pub refundable: Option<bool>,
+    pub min_stay: Option<Stay>,
+    pub max_stay: Option<Stay>,
+    pub advance_purchase_days: Option<u32>,
+}
+
+impl RuleFacts {
+    pub fn extract(rules: &FareRuleSet) -> Self {
+        let text = |n: u32| rules.category_text(n).map(|t| normalize(&t));
+
+        let penalties = text(category::PENALTIES);
+        let changes = text(category::VOLUNTARY_CHANGES).or_else(|| penalties.clone());
+        let refunds = text(category::VOLUNTARY_REFUNDS).or_else(|| penalties.clone());
+
+        Self {
+            change_fee: changes.as_deref().and_then(change_fee),
+            refundable: refunds.as_deref().and_then(refundable),
+            min_stay: text(category::MINIMUM_STAY).as_deref().and_then(min_stay),
+            max_stay: text(category::MAXIMUM_STAY).as_deref().and_then(max_stay),
+            advance_purchase_days: text(category::ADVANCE_PURCHASE)
+                .as_deref()
+                .and_then(advance_purchase),
+        }
+    }
+}
+
+/// Uppercase and collapse whitespace so phrases match across line breaks.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_uppercase()
+}
+
+/// Split into sentences so a fee is tied to the phrase mentioning it. A
+/// period only ends a sentence when followed by a space, so amounts such as
+/// `200.00` stay intact.
+fn clauses(text: &str) -> impl Iterator<Item = &str> {
+    text.split(". ")
+        .flat_map(|s| s.split(';'))
+        .map(|s| s.trim().trim_end_matches('.'))
+        .filter(|c| !c.is_empty())
+}
+
+fn change_fee(text: &str) -> Option<ChangeFee> {
+    for clause in clauses(text) {
+        let about_changes = ["CHANGE", "REISSUE", "REBOOK", "REVALIDATION"]
+            .iter()
+            .any(|w| clause.contains(w));
+        if !about_changes {
+            continue;
+        }
+        if clause.contains("NOT PERMITTED") {
+            return Some(ChangeFee::NotPermitted);
+        }
+        if clause.contains("WITHOUT CHARGE")
+            || clause.contains("FREE OF CHARGE")
+            || clause.contains("NO CHARGE")
+        {
+            return Some(ChangeFee::Free);
+        }
+        if let Some(fee) = first_money(clause) {
+            return Some(ChangeFee::Amount(fee));
+        }
+    }
+    None
+}
+
+fn refundable(text: &str) -> Option<bool> {
+    const NON_REFUNDABLE: &[&str] = &[
+        "NON-REFUNDABLE",
+        "NONREFUNDABLE",
+        "NON REFUNDABLE",
+        "NO REFUND",
+        "CANCELLATIONS NOT PERMITTED",
+        "REFUND NOT PERMITTED",
+        "REFUNDS NOT PERMITTED",
+    ];
+    const REFUNDABLE: &[&str] = &[
+        "FULLY REFUNDABLE",
+        "TICKET IS REFUNDABLE",
+        "REFUND PERMITTED",
+        "REFUNDS PERMITTED",
+        "CANCELLATIONS PERMITTED",
+    ];
+
+    if NON_REFUNDABLE.iter().any(|p| text.contains(p)) {
+        Some(false)
+    } else if REFUNDABLE.iter().any(|p| text.contains(p)) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn min_stay(text: &str) -> Option<Stay> {
+    if text.contains("NO MINIMUM STAY") {
+        return Some(Stay::None);
+    }
+    if text.contains("SATURDAY NIGHT") || text.contains("SAT NIGHT") {
+        return Some(Stay::SaturdayNight);
+    }
+    first_duration(text)
+}
+
+fn max_stay(text: &str) -> Option<Stay> {
+    if text.contains("NO MAXIMUM STAY") {
+        return Some(Stay::None);
+    }
+    first_duration(text)
+}
+
+fn advance_purchase(text: &str) -> Option<u32> {
+    if text.contains("NO ADVANCE") {
+        return Some(0);
+    }
+    match first_duration(text)? {
+        Stay::Days(n) => Some(n),
+        _ => None,
+    }
+}
+
+/// First `<n> DAYS` / `<n> MONTHS` phrase.
+fn first_duration(text: &str) -> Option<Stay> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.windows(2).find_map(|w| {
+        let n: u32 = w[0].parse().ok()?;
+        let unit = w[1].trim_end_matches([',', ')']);
+        match unit {
+            "DAY" | "DAYS" => Some(Stay::Days(n)),
+            "MONTH" | "MONTHS" => Some(Stay::Months(n)),
+            _ => None,
+        }
+    })
+}
+
+/// First amount written as `USD 200.00` or `USD200`.
+fn first_money(text: &str) -> Option<Money> {
+    // Three-letter words that precede numbers in rules text without being
+    // currencies.
+    const NOT_CURRENCIES: &[&str] = &[
+        "AND", "ANY", "ARE", "DAY", "FOR", "NOT", "ONE", "PER", "THE", "TWO",
+    ];
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    for (i, word) in words.iter().enumerate() {
+        let word = word.trim_end_matches(',');
+        let Some(code) = word.get(..word.len().min(3)) else {
+            continue;
+        };
+        if NOT_CURRENCIES.contains(&code) {
+            continue;
+        }
+        let candidate = if word.len() == 3 {
+            match words.get(i + 1) {
+                Some(next) => format!("{word}{}", next.trim_end_matches(',')),
+                None => continue,
+            }
+        } else {
+            word.to_string()
+        };
+        if let Ok(money) = Money::parse(&candidate) {
+            return Some(money);
+        }
+    }
+    None
+}
+
+fn text_or_lines<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Text {
+        One(String),
+        Lines(Vec<String>),
+    }
+
+    Ok(match Option::<Text>::deserialize(deserializer)? {
+        Some(Text::One(s)) => s,
+        Some(Text::Lines(lines)) => lines.join("\n"),
+        None => String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES_JSON: &str = r#"{
+        "fares": [{
+            "carrier": "AA",
+            "code": "OUA0AQBN",
+            "originCity": "PHX",
+            "destinationCity": "DTT",
+            "rules": [
+                { "title": "05. ADVANCE RESERVATIONS/TICKETING",
+                  "text": "RESERVATIONS ARE REQUIRED FOR ALL SECTORS.\nTICKETING MUST BE COMPLETED AT LEAST 7 DAYS BEFORE DEPARTURE." },
+                { "category": 6, "text": "NO MINIMUM STAY REQUIREMENTS APPLY." },
+                { "title": "MAXIMUM STAY",
+                  "text": ["TRAVEL FROM LAST STOPOVER MUST COMMENCE", "NO LATER THAN 12 MONTHS AFTER DEPARTURE."] },
+                { "title": "16. PENALTIES",
+                  "text": "CANCELLATIONS\n  TICKET IS NON-REFUNDABLE.\nCHANGES\n  ANY TIME CHARGE USD 200.00 FOR REISSUE/REVALIDATION." },
+                { "title": "SOMETHING ELSE", "text": "..." }
+            ]
+        }]
+    }"#;
+
+    fn rules() -> FareRules {
+        serde_json::from_str(RULES_JSON).unwrap()
+    }
+
+    #[test]
+    fn groups_by_category() {
+        let rules = rules();
+        let fare = rules.fare("OUA0AQBN").unwrap();
+        let groups = fare.by_category();
+
+        assert_eq!(
+            groups.keys().copied().collect::<Vec<_>>(),
+            [
+                category::ADVANCE_PURCHASE,
+                category::MINIMUM_STAY,
+                category::MAXIMUM_STAY,
+                category::PENALTIES
+            ]
+        );
+        assert_eq!(
+            groups[&category::MINIMUM_STAY][0].display_title(),
+            Some("MINIMUM STAY")
+        );
+    }
+
+    #[test]
+    fn extracts_key_facts() {
+        let facts = rules().fares[0].facts();
+
+        assert_eq!(
+            facts.change_fee,
+            Some(ChangeFee::Amount("USD200.00".parse().unwrap()))
+        );
+        assert_eq!(facts.refundable, Some(false));
+        assert_eq!(facts.min_stay, Some(Stay::None));
+        assert_eq!(facts.max_stay, Some(Stay::Months(12)));
+        assert_eq!(facts.advance_purchase_days, Some(7));
+    }
+
+    #[test]
+    fn free_changes_and_saturday_stay() {
+        let text = normalize("CHANGES PERMITTED ANY TIME WITHOUT CHARGE.");
+        assert_eq!(change_fee(&text), Some(ChangeFee::Free));
+        assert_eq!(
+            min_stay(&normalize("SATURDAY NIGHT STAY REQUIRED")),
+            Some(Stay::SaturdayNight)
+        );
+        assert_eq!(first_money("CHARGE FOR THE CHANGE"), None);
+    }
+
+    #[test]
+    fn multibyte_text_is_skipped() {
+        let text = normalize("CHANGES PERMITTED FOR A FEE OF 50€ PER TICKET.");
+        assert_eq!(first_money(&text), None);
+        assert_eq!(
+            first_money("€€ FEE OF USD 75"),
+            Some("USD75".parse().unwrap())
+        );
+        assert_eq!(first_money("FEE OF 50 €"), None);
+    }
+}
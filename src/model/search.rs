@@ -4,7 +4,8 @@ use serde_json::Value;
 use super::common::{
     Cabin, DateModifier, DayOfWeek, Page, Pax, SearchFilter, SearchName, SliceFilter,
 };
-use super::response::CarrierStopMatrix;
+use super::money::Money;
+use super::response::{Airport, Carrier, CarrierStopMatrix, Distance, Segment, SliceExt};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -242,6 +243,8 @@ pub struct SearchRequestBuilder {
     slices: Vec<SliceSpec>,
     cabin: Option<Cabin>,
     adults: Option<u8>,
+    pax: Option<Pax>,
+    filter: Option<SearchFilter>,
     page_size: Option<u32>,
     max_legs_relative_to_min: Option<u32>,
     change_of_airport: Option<bool>,
@@ -314,6 +317,18 @@ impl SearchRequestBuilder {
         self
     }
 
+    /// All passenger counts. A later or earlier `adults` call still wins for
+    /// the adult count.
+    pub fn pax(mut self, pax: Pax) -> Self {
+        self.pax = Some(pax);
+        self
+    }
+
+    pub fn filter(mut self, filter: SearchFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
     pub fn page_size(mut self, size: u32) -> Self {
         self.page_size = Some(size);
         self
@@ -334,6 +349,7 @@ impl SearchRequestBuilder {
         self
     }
 
+    /// Routing code for every slice without its own [`slice_routing`](Self::slice_routing).
     pub fn route_language(mut self, rl: &str) -> Self {
         self.route_language = Some(rl.to_string());
         self
@@ -343,6 +359,8 @@ impl SearchRequestBuilder {
         self.route_language(&code.build())
     }
 
+    /// Extension code for every slice without its own
+    /// [`slice_extension`](Self::slice_extension).
     pub fn command_line(mut self, cl: &str) -> Self {
         self.command_line = Some(cl.to_string());
         self
@@ -352,6 +370,26 @@ impl SearchRequestBuilder {
         self.command_line(&code.build())
     }
 
+    /// Routing code for the most recently added slice only.
+    pub fn slice_routing(mut self, code: super::routing::RoutingCode) -> Self {
+        let slice = self
+            .slices
+            .last_mut()
+            .expect("slice_routing needs a preceding add_slice");
+        slice.route_language = Some(code.build());
+        self
+    }
+
+    /// Extension code for the most recently added slice only.
+    pub fn slice_extension(mut self, code: super::routing::ExtensionCode) -> Self {
+        let slice = self
+            .slices
+            .last_mut()
+            .expect("slice_extension needs a preceding add_slice");
+        slice.command_line = Some(code.build());
+        self
+    }
+
     pub fn bg_program_response(mut self, token: &str) -> Self {
         self.bg_program_response = Some(token.to_string());
         self
@@ -391,25 +429,24 @@ impl SearchRequestBuilder {
                     is_arrival_date: false,
                     filter: SliceFilter::default(),
                     selected: false,
-                    route_language: slice.route_language,
-                    command_line: slice.command_line,
+                    route_language: slice.route_language.or_else(|| self.route_language.clone()),
+                    command_line: slice.command_line.or_else(|| self.command_line.clone()),
                 })
                 .collect()
         };
 
         let inputs = SearchInputs {
-            filter: SearchFilter::default(),
+            filter: self.filter.unwrap_or_default(),
             page: Page {
                 current: 1,
                 size: self.page_size.unwrap_or(25),
             },
             pax: Pax {
-                adults: self.adults.unwrap_or(1),
-                children: 0,
-                infants_in_lap: 0,
-                infants_in_seat: 0,
-                seniors: 0,
-                youth: 0,
+                adults: self
+                    .adults
+                    .or(self.pax.as_ref().map(|p| p.adults))
+                    .unwrap_or(1),
+                ..self.pax.unwrap_or_default()
             },
             slices,
             first_day_of_week: DayOfWeek::Sunday,
@@ -481,12 +518,187 @@ pub struct SolutionList {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Solution {
     pub id: String,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub display_price: Option<Money>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub display_total: Option<Money>,
+    #[serde(default)]
+    pub passenger_count: Option<u32>,
     #[serde(default)]
-    pub display_price: Option<String>,
+    pub ext: Option<SolutionExt>,
     #[serde(default)]
-    pub display_total: Option<String>,
+    pub itinerary: Option<SolutionItinerary>,
     #[serde(flatten)]
     pub data: Value,
 }
+
+impl Solution {
+    /// Total price for all passengers, whichever of the price fields is present.
+    pub fn price(&self) -> Option<Money> {
+        self.display_total
+            .or_else(|| self.ext.as_ref().and_then(|e| e.total_price))
+            .or(self.display_price)
+    }
+
+    pub fn slices(&self) -> &[SolutionSlice] {
+        self.itinerary
+            .as_ref()
+            .map(|it| it.slices.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Marketing carriers across the whole itinerary, falling back to the
+    /// per-slice segments when the summary `carriers` list is absent.
+    pub fn carriers(&self) -> Vec<&Carrier> {
+        if let Some(it) = &self.itinerary
+            && !it.carriers.is_empty()
+        {
+            return it.carriers.iter().collect();
+        }
+
+        let mut carriers: Vec<&Carrier> = Vec::new();
+        for segment in self.slices().iter().flat_map(|s| &s.segments) {
+            if let Some(c) = &segment.carrier
+                && !carriers.iter().any(|seen| seen.code == c.code)
+            {
+                carriers.push(c);
+            }
+        }
+        carriers
+    }
+
+    /// Total stops across all slices.
+    pub fn stop_count(&self) -> usize {
+        self.slices().iter().map(SolutionSlice::stop_count).sum()
+    }
+
+    /// Total elapsed minutes across all slices.
+    pub fn duration(&self) -> Option<u32> {
+        self.slices().iter().map(|s| s.duration).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionExt {
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub total_price: Option<Money>,
+    #[serde(default, deserialize_with = "super::money::lenient")]
+    pub price_per_mile: Option<Money>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionItinerary {
+    #[serde(default)]
+    pub carriers: Vec<Carrier>,
+    #[serde(default)]
+    pub single_carrier: Option<Carrier>,
+    #[serde(default)]
+    pub distance: Option<Distance>,
+    #[serde(default)]
+    pub slices: Vec<SolutionSlice>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// One slice of a solution-list entry.
+///
+/// The solution list summarizes each slice with `flights` and `stops`;
+/// `segments` is only populated when the summarizer includes them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionSlice {
+    #[serde(default)]
+    pub origin: Option<Airport>,
+    #[serde(default)]
+    pub destination: Option<Airport>,
+    #[serde(default)]
+    pub departure: Option<String>,
+    #[serde(default)]
+    pub arrival: Option<String>,
+    #[serde(default)]
+    pub duration: Option<u32>,
+    #[serde(default)]
+    pub flights: Vec<String>,
+    #[serde(default)]
+    pub stops: Vec<Airport>,
+    #[serde(default)]
+    pub cabins: Vec<String>,
+    #[serde(default)]
+    pub ext: Option<SliceExt>,
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+impl SolutionSlice {
+    pub fn stop_count(&self) -> usize {
+        if !self.stops.is_empty() {
+            self.stops.len()
+        } else if !self.segments.is_empty() {
+            self.segments.len() - 1
+        } else {
+            self.flights.len().saturating_sub(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLUTION_JSON: &str = r#"{
+        "id": "abc123",
+        "displayTotal": "USD224.20",
+        "passengerCount": 1,
+        "ext": { "totalPrice": "USD224.20", "pricePerMile": "USD0.12" },
+        "itinerary": {
+            "carriers": [{ "code": "AA", "shortName": "American" }],
+            "singleCarrier": { "code": "AA", "shortName": "American" },
+            "slices": [{
+                "origin": { "code": "PHX", "name": "Phoenix Sky Harbor" },
+                "destination": { "code": "DTW", "name": "Detroit Metro" },
+                "departure": "2026-02-15T06:00-07:00",
+                "arrival": "2026-02-15T15:55-05:00",
+                "duration": 415,
+                "flights": ["AA1234", "AA567"],
+                "stops": [{ "code": "DFW", "name": "Dallas/Fort Worth" }],
+                "cabins": ["COACH"]
+            }]
+        }
+    }"#;
+
+    #[test]
+    fn solution_typed_fields() {
+        let solution: Solution = serde_json::from_str(SOLUTION_JSON).unwrap();
+        assert_eq!(solution.price(), Some("USD224.20".parse().unwrap()));
+        assert_eq!(solution.passenger_count, Some(1));
+        assert_eq!(solution.slices().len(), 1);
+        assert_eq!(solution.stop_count(), 1);
+        assert_eq!(solution.duration(), Some(415));
+
+        let slice = &solution.slices()[0];
+        assert_eq!(slice.flights, ["AA1234", "AA567"]);
+        assert_eq!(slice.stops[0].code.as_deref(), Some("DFW"));
+
+        let carriers = solution.carriers();
+        assert_eq!(carriers.len(), 1);
+        assert_eq!(carriers[0].short_name.as_deref(), Some("American"));
+    }
+
+    #[test]
+    fn unparseable_price_falls_back() {
+        let json =
+            SOLUTION_JSON.replace(r#""displayTotal": "USD224.20""#, r#""displayTotal": "N/A""#);
+        let solution: Solution = serde_json::from_str(&json).unwrap();
+        assert_eq!(solution.display_total, None);
+        assert_eq!(solution.price(), Some("USD224.20".parse().unwrap()));
+    }
+}
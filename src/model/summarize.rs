@@ -3,6 +3,7 @@ use serde_json::Value;
 
 use super::common::{Cabin, DayOfWeek, Page, Pax, SearchFilter};
 use super::response::BookingDetails;
+use super::rules::FareRules;
 use super::search::{ApiErrorBody, DEFAULT_SUMMARIZERS};
 
 #[derive(Debug, Clone, Serialize)]
@@ -107,7 +108,7 @@ pub struct SummarizeResponse {
     #[serde(default)]
     pub booking_details: Option<BookingDetails>,
     #[serde(default)]
-    pub fare_rules: Option<Value>,
+    pub fare_rules: Option<FareRules>,
     #[serde(flatten)]
     pub data: Value,
 }
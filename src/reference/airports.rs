@@ -0,0 +1,171 @@
+use std::sync::OnceLock;
+
+use super::{ReferenceError, parse_table};
+use crate::model::location::LatLng;
+use crate::model::search::SearchRequestBuilder;
+
+const AIRPORTS_CSV: &str = include_str!("data/airports.csv");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AirportInfo {
+    pub code: String,
+    pub name: String,
+    /// IATA city code; the airport code itself for single-airport cities.
+    pub city_code: String,
+    pub city: String,
+    /// ISO 3166-1 alpha-2.
+    pub country: String,
+    pub position: LatLng,
+    /// IANA time zone name, e.g. `"America/New_York"`.
+    pub timezone: String,
+}
+
+impl AirportInfo {
+    pub fn distance_km(&self, other: &AirportInfo) -> f64 {
+        self.position.distance_km(&other.position)
+    }
+}
+
+/// Airports in table order.
+#[derive(Debug, Clone, Default)]
+pub struct AirportDb {
+    airports: Vec<AirportInfo>,
+}
+
+impl AirportDb {
+    /// The table shipped with the crate.
+    pub fn embedded() -> &'static Self {
+        static DB: OnceLock<AirportDb> = OnceLock::new();
+        DB.get_or_init(|| Self::from_csv(AIRPORTS_CSV).expect("embedded airport table is valid"))
+    }
+
+    /// Parse a `code,name,city_code,city,country,latitude,longitude,timezone`
+    /// table with a header row.
+    pub fn from_csv(data: &str) -> Result<Self, ReferenceError> {
+        let mut airports = Vec::new();
+        for (line, fields) in parse_table(data, 8)? {
+            let coordinate = |value: &str| {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| ReferenceError::InvalidRecord {
+                        line,
+                        reason: format!("invalid coordinate {value:?}"),
+                    })
+            };
+            airports.push(AirportInfo {
+                code: fields[0].to_string(),
+                name: fields[1].to_string(),
+                city_code: fields[2].to_string(),
+                city: fields[3].to_string(),
+                country: fields[4].to_string(),
+                position: LatLng {
+                    latitude: coordinate(fields[5])?,
+                    longitude: coordinate(fields[6])?,
+                },
+                timezone: fields[7].to_string(),
+            });
+        }
+        Ok(Self { airports })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AirportInfo> {
+        self.airports.iter()
+    }
+
+    pub fn get(&self, code: &str) -> Option<&AirportInfo> {
+        self.iter().find(|a| a.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Airports serving an IATA city code, e.g. `"NYC"`.
+    pub fn city<'a>(&'a self, city_code: &'a str) -> impl Iterator<Item = &'a AirportInfo> {
+        self.iter()
+            .filter(move |a| a.city_code.eq_ignore_ascii_case(city_code))
+    }
+
+    /// Airports within `radius_km` of `code`, nearest first, including
+    /// `code` itself. Empty if `code` is not in the table.
+    pub fn nearby(&self, code: &str, radius_km: f64) -> Vec<(&AirportInfo, f64)> {
+        let Some(center) = self.get(code) else {
+            return Vec::new();
+        };
+        let mut nearby: Vec<_> = self
+            .iter()
+            .map(|a| (a, center.distance_km(a)))
+            .filter(|&(_, km)| km <= radius_km)
+            .collect();
+        nearby.sort_by(|a, b| a.1.total_cmp(&b.1));
+        nearby
+    }
+}
+
+/// Codes of the airports within `radius_km` of `code` in the embedded
+/// table, nearest first and starting with `code`.
+pub fn nearby_airports(code: &str, radius_km: f64) -> Vec<&'static str> {
+    AirportDb::embedded()
+        .nearby(code, radius_km)
+        .into_iter()
+        .map(|(a, _)| a.code.as_str())
+        .collect()
+}
+
+impl SearchRequestBuilder {
+    /// Set origins to `code` and every airport within `km` of it. Codes
+    /// missing from the embedded table are used on their own.
+    pub fn origins_within(self, code: &str, km: f64) -> Self {
+        let codes = within(code, km);
+        self.origins(&codes)
+    }
+
+    /// Like [`origins_within`](Self::origins_within), for destinations.
+    pub fn destinations_within(self, code: &str, km: f64) -> Self {
+        let codes = within(code, km);
+        self.destinations(&codes)
+    }
+}
+
+fn within(code: &str, km: f64) -> Vec<&str> {
+    let codes = nearby_airports(code, km);
+    if codes.is_empty() { vec![code] } else { codes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::search::SearchRequest;
+
+    #[test]
+    fn lookups() {
+        let db = AirportDb::embedded();
+        let jfk = db.get("jfk").unwrap();
+        assert_eq!(jfk.city_code, "NYC");
+        assert_eq!(jfk.timezone, "America/New_York");
+        let nyc: Vec<_> = db.city("NYC").map(|a| a.code.as_str()).collect();
+        assert_eq!(nyc, ["JFK", "LGA", "EWR"]);
+    }
+
+    #[test]
+    fn distances() {
+        let db = AirportDb::embedded();
+        let km = db.get("LHR").unwrap().distance_km(db.get("JFK").unwrap());
+        assert!((km - 5540.0).abs() < 20.0, "{km}");
+    }
+
+    #[test]
+    fn nearby_is_sorted_and_bounded() {
+        assert_eq!(nearby_airports("SFO", 60.0), ["SFO", "OAK", "SJC"]);
+        assert_eq!(nearby_airports("JFK", 40.0), ["JFK", "LGA", "EWR"]);
+        assert!(nearby_airports("XXX", 100.0).is_empty());
+    }
+
+    #[test]
+    fn builder_fills_origins() {
+        let request: SearchRequest = SearchRequest::builder()
+            .origins_within("SFO", 60.0)
+            .destinations_within("XXX", 100.0)
+            .date("2025-06-01")
+            .build();
+        let slice = &request.inputs.slices[0];
+        assert_eq!(slice.origins, ["SFO", "OAK", "SJC"]);
+        assert_eq!(slice.destinations, ["XXX"]);
+    }
+}
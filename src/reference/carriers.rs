@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ReferenceError, parse_table};
+use crate::model::response::Carrier;
+use crate::model::routing::{Alliance, ExtensionCode, ExtensionCommand};
+use crate::model::search::Solution;
+
+const CARRIERS_CSV: &str = include_str!("data/carriers.csv");
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarrierInfo {
+    /// IATA designator, e.g. `"AA"`.
+    pub code: String,
+    pub name: String,
+    #[serde(default)]
+    pub alliance: Option<Alliance>,
+    #[serde(default)]
+    pub low_cost: bool,
+}
+
+/// Carriers keyed by IATA code.
+#[derive(Debug, Clone, Default)]
+pub struct CarrierDb {
+    carriers: BTreeMap<String, CarrierInfo>,
+}
+
+impl CarrierDb {
+    /// The table shipped with the crate.
+    pub fn embedded() -> &'static Self {
+        static DB: OnceLock<CarrierDb> = OnceLock::new();
+        DB.get_or_init(|| Self::from_csv(CARRIERS_CSV).expect("embedded carrier table is valid"))
+    }
+
+    /// Parse a `code,name,alliance,low_cost` table with a header row.
+    pub fn from_csv(data: &str) -> Result<Self, ReferenceError> {
+        let mut db = Self::default();
+        for (line, fields) in parse_table(data, 4)? {
+            let invalid = |reason: String| ReferenceError::InvalidRecord { line, reason };
+            let alliance = match fields[2] {
+                "" => None,
+                code => Some(
+                    Alliance::from_code(code)
+                        .ok_or_else(|| invalid(format!("unknown alliance {code:?}")))?,
+                ),
+            };
+            let low_cost = fields[3]
+                .parse()
+                .map_err(|_| invalid(format!("invalid low_cost {:?}", fields[3])))?;
+            db.insert(CarrierInfo {
+                code: fields[0].to_string(),
+                name: fields[1].to_string(),
+                alliance,
+                low_cost,
+            });
+        }
+        Ok(db)
+    }
+
+    /// Add or replace carriers from a JSON array of [`CarrierInfo`].
+    pub fn load_overrides(&mut self, path: impl AsRef<Path>) -> Result<(), ReferenceError> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).map_err(|source| ReferenceError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let records: Vec<CarrierInfo> = serde_json::from_str(&data)?;
+        for record in records {
+            self.insert(record);
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, mut info: CarrierInfo) {
+        info.code.make_ascii_uppercase();
+        self.carriers.insert(info.code.clone(), info);
+    }
+
+    pub fn get(&self, code: &str) -> Option<&CarrierInfo> {
+        self.carriers.get(&code.to_ascii_uppercase())
+    }
+
+    pub fn alliance_of(&self, code: &str) -> Option<Alliance> {
+        self.get(code).and_then(|c| c.alliance)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CarrierInfo> {
+        self.carriers.values()
+    }
+
+    pub fn members(&self, alliance: Alliance) -> impl Iterator<Item = &CarrierInfo> {
+        self.iter().filter(move |c| c.alliance == Some(alliance))
+    }
+
+    /// Fill in `short_name` (and `name`) from the table when the response
+    /// left them out.
+    pub fn fill_carrier(&self, carrier: &mut Carrier) {
+        let Some(info) = carrier.code.as_deref().and_then(|code| self.get(code)) else {
+            return;
+        };
+        carrier.short_name.get_or_insert_with(|| info.name.clone());
+        carrier.name.get_or_insert_with(|| info.name.clone());
+    }
+
+    /// [`fill_carrier`](Self::fill_carrier) for every carrier in the
+    /// solutions.
+    pub fn fill_solutions(&self, solutions: &mut [Solution]) {
+        for itinerary in solutions.iter_mut().filter_map(|s| s.itinerary.as_mut()) {
+            let segments = itinerary.slices.iter_mut().flat_map(|s| &mut s.segments);
+            for carrier in itinerary
+                .carriers
+                .iter_mut()
+                .chain(itinerary.single_carrier.as_mut())
+                .chain(segments.filter_map(|s| s.carrier.as_mut()))
+            {
+                self.fill_carrier(carrier);
+            }
+        }
+    }
+
+    /// The alliance every marketing carrier of the solution belongs to, or
+    /// `None` for mixed, unaligned or unknown carriers.
+    pub fn solution_alliance(&self, solution: &Solution) -> Option<Alliance> {
+        let mut alliances = solution
+            .carriers()
+            .into_iter()
+            .map(|c| c.code.as_deref().and_then(|code| self.alliance_of(code)));
+        let first = alliances.next()??;
+        alliances.all(|a| a == Some(first)).then_some(first)
+    }
+
+    pub fn group_by_alliance<'a>(
+        &self,
+        solutions: &'a [Solution],
+    ) -> BTreeMap<Option<Alliance>, Vec<&'a Solution>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for solution in solutions {
+            groups
+                .entry(self.solution_alliance(solution))
+                .or_default()
+                .push(solution);
+        }
+        groups
+    }
+}
+
+impl ExtensionCode {
+    /// Replace `ALLIANCE` commands with `AIRLINES` listing the members
+    /// known to `db`.
+    #[must_use]
+    pub fn expand_alliances(self, db: &CarrierDb) -> Self {
+        self.commands()
+            .iter()
+            .map(|command| match command {
+                ExtensionCommand::Alliances(alliances) => ExtensionCommand::Airlines(
+                    alliances
+                        .iter()
+                        .flat_map(|&a| db.members(a))
+                        .map(|c| c.code.clone())
+                        .collect(),
+                ),
+                other => other.clone(),
+            })
+            .fold(ExtensionCode::new(), ExtensionCode::command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_lookups() {
+        let db = CarrierDb::embedded();
+        let aa = db.get("aa").unwrap();
+        assert_eq!(aa.name, "American Airlines");
+        assert_eq!(aa.alliance, Some(Alliance::OneWorld));
+        assert!(db.get("NK").unwrap().low_cost);
+        assert!(db.members(Alliance::StarAlliance).any(|c| c.code == "UA"));
+        assert_eq!(db.alliance_of("EK"), None);
+    }
+
+    #[test]
+    fn overrides_replace_and_add() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("carriers.json");
+        std::fs::write(
+            &path,
+            r#"[{"code": "az", "name": "ITA Airways", "alliance": "star-alliance"},
+                {"code": "ZZ", "name": "Test Air", "lowCost": true}]"#,
+        )
+        .unwrap();
+
+        let mut db = CarrierDb::embedded().clone();
+        db.load_overrides(&path).unwrap();
+        assert_eq!(db.alliance_of("AZ"), Some(Alliance::StarAlliance));
+        assert!(db.get("ZZ").unwrap().low_cost);
+    }
+
+    #[test]
+    fn invalid_rows_report_line() {
+        let err = CarrierDb::from_csv("code,name,alliance,low_cost\nAA,American,nope,false\n")
+            .unwrap_err();
+        assert!(matches!(err, ReferenceError::InvalidRecord { line: 2, .. }));
+    }
+
+    #[test]
+    fn expand_alliance_and_group() {
+        let db = CarrierDb::embedded();
+        let code = ExtensionCode::new()
+            .max_stops(1)
+            .alliance(Alliance::SkyTeam)
+            .expand_alliances(db);
+        let airlines = code.build();
+        assert!(airlines.starts_with("MAXSTOPS 1;AIRLINES "));
+        assert!(airlines.contains(" DL") && !airlines.contains("AA"));
+
+        let solutions: Vec<Solution> = serde_json::from_value(serde_json::json!([
+            { "id": "1", "itinerary": { "carriers": [{ "code": "AA" }, { "code": "BA" }] } },
+            { "id": "2", "itinerary": { "carriers": [{ "code": "AA" }, { "code": "UA" }] } },
+            { "id": "3", "itinerary": { "carriers": [{ "code": "UA" }] } },
+        ]))
+        .unwrap();
+        let groups = db.group_by_alliance(&solutions);
+        assert_eq!(groups[&Some(Alliance::OneWorld)][0].id, "1");
+        assert_eq!(groups[&None][0].id, "2");
+        assert_eq!(groups[&Some(Alliance::StarAlliance)][0].id, "3");
+
+        let mut solutions = solutions;
+        db.fill_solutions(&mut solutions);
+        let carriers = solutions[2].carriers();
+        assert_eq!(carriers[0].short_name.as_deref(), Some("United Airlines"));
+    }
+}
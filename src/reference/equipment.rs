@@ -0,0 +1,193 @@
+use std::sync::OnceLock;
+
+use super::{ReferenceError, parse_table};
+use crate::model::routing::{AircraftCategory, AircraftSpec, ExtensionCode, ExtensionCommand};
+
+const EQUIPMENT_CSV: &str = include_str!("data/equipment.csv");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquipmentInfo {
+    /// IATA type code as used in extension codes and responses, e.g. `"77W"`,
+    /// or a generic family code such as `"777"`.
+    pub iata: String,
+    /// ICAO type designator, e.g. `"B77W"`. `None` for generic family codes,
+    /// which have no single designator.
+    pub icao: Option<String>,
+    pub manufacturer: String,
+    /// Family name, e.g. `"777"` or `"A320"`.
+    pub family: String,
+    pub category: AircraftCategory,
+    /// Typical seats in a two- or three-class layout.
+    pub seats: u32,
+    pub widebody: bool,
+}
+
+/// Aircraft types in table order.
+#[derive(Debug, Clone, Default)]
+pub struct EquipmentDb {
+    types: Vec<EquipmentInfo>,
+}
+
+impl EquipmentDb {
+    /// The table shipped with the crate.
+    pub fn embedded() -> &'static Self {
+        static DB: OnceLock<EquipmentDb> = OnceLock::new();
+        DB.get_or_init(|| Self::from_csv(EQUIPMENT_CSV).expect("embedded equipment table is valid"))
+    }
+
+    /// Parse an `iata,icao,manufacturer,family,category,seats,widebody`
+    /// table with a header row.
+    pub fn from_csv(data: &str) -> Result<Self, ReferenceError> {
+        let mut types = Vec::new();
+        for (line, fields) in parse_table(data, 7)? {
+            let invalid = |field: &str, value: &str| ReferenceError::InvalidRecord {
+                line,
+                reason: format!("invalid {field} {value:?}"),
+            };
+            types.push(EquipmentInfo {
+                iata: fields[0].to_string(),
+                icao: (!fields[1].is_empty()).then(|| fields[1].to_string()),
+                manufacturer: fields[2].to_string(),
+                family: fields[3].to_string(),
+                category: AircraftCategory::from_code(fields[4])
+                    .ok_or_else(|| invalid("category", fields[4]))?,
+                seats: fields[5].parse().map_err(|_| invalid("seats", fields[5]))?,
+                widebody: fields[6]
+                    .parse()
+                    .map_err(|_| invalid("widebody", fields[6]))?,
+            });
+        }
+        Ok(Self { types })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EquipmentInfo> {
+        self.types.iter()
+    }
+
+    pub fn by_iata(&self, code: &str) -> Option<&EquipmentInfo> {
+        self.iter().find(|t| t.iata.eq_ignore_ascii_case(code))
+    }
+
+    /// The first type with this ICAO designator; a few designators cover
+    /// more than one IATA code (`B738` is both `738` and `73H`, the
+    /// winglet-equipped 737-800).
+    pub fn by_icao(&self, code: &str) -> Option<&EquipmentInfo> {
+        self.iter().find(|t| {
+            t.icao
+                .as_deref()
+                .is_some_and(|i| i.eq_ignore_ascii_case(code))
+        })
+    }
+
+    pub fn family<'a>(&'a self, family: &'a str) -> impl Iterator<Item = &'a EquipmentInfo> {
+        self.iter()
+            .filter(move |t| t.family.eq_ignore_ascii_case(family))
+    }
+
+    pub fn widebodies(&self) -> impl Iterator<Item = &EquipmentInfo> {
+        self.iter().filter(|t| t.widebody)
+    }
+
+    pub fn category(&self, category: AircraftCategory) -> impl Iterator<Item = &EquipmentInfo> {
+        self.iter().filter(move |t| t.category == category)
+    }
+}
+
+impl ExtensionCode {
+    /// `AIRCRAFT` with every IATA type code of the family in the embedded
+    /// equipment table, e.g. `"787"` or `"A350"`. Fails if the family is not
+    /// in the table.
+    pub fn aircraft_family(self, family: &str) -> Result<Self, ReferenceError> {
+        let specs: Vec<_> = EquipmentDb::embedded()
+            .family(family)
+            .map(|t| AircraftSpec::Type(t.iata.clone()))
+            .collect();
+        if specs.is_empty() {
+            return Err(ReferenceError::UnknownFamily(family.to_string()));
+        }
+        Ok(self.command(ExtensionCommand::Aircraft(specs)))
+    }
+
+    /// `AIRCRAFT` with every widebody type code in the embedded equipment
+    /// table.
+    #[must_use]
+    pub fn widebody_only(self) -> Self {
+        let specs = EquipmentDb::embedded()
+            .widebodies()
+            .map(|t| AircraftSpec::Type(t.iata.clone()))
+            .collect();
+        self.command(ExtensionCommand::Aircraft(specs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_both_ways() {
+        let db = EquipmentDb::embedded();
+        let b77w = db.by_iata("77w").unwrap();
+        assert_eq!(b77w.icao.as_deref(), Some("B77W"));
+        assert_eq!(b77w.family, "777");
+        assert!(b77w.widebody);
+        assert_eq!(db.by_icao("A21N").unwrap().iata, "32Q");
+        assert_eq!(db.by_icao("B738").unwrap().iata, "738");
+        assert_eq!(db.by_iata("73H").unwrap().icao.as_deref(), Some("B738"));
+        assert_eq!(db.by_iata("787").unwrap().icao, None);
+        assert_eq!(
+            db.by_iata("DH4").unwrap().category,
+            AircraftCategory::Turboprop
+        );
+        assert!(
+            db.category(AircraftCategory::Turboprop)
+                .all(|t| !t.widebody)
+        );
+    }
+
+    #[test]
+    fn aircraft_family_expands() {
+        let code = ExtensionCode::new().aircraft_family("787").unwrap().build();
+        assert_eq!(code, "AIRCRAFT T:788 T:789 T:781 T:787");
+        assert!(ExtensionCode::parse(&code).is_ok());
+
+        let code = ExtensionCode::new().widebody_only().build();
+        assert!(code.contains("T:359") && !code.contains("T:320"));
+    }
+
+    #[test]
+    fn unknown_family_is_an_error() {
+        let err = ExtensionCode::new()
+            .aircraft_family("Concorde")
+            .unwrap_err();
+        assert!(matches!(err, ReferenceError::UnknownFamily(f) if f == "Concorde"));
+    }
+
+    #[test]
+    fn routing_aircraft_constants_resolve() {
+        use crate::model::routing::aircraft::*;
+
+        let db = EquipmentDb::embedded();
+        for code in [
+            AIRBUS_A320_FAMILY,
+            AIRBUS_A320NEO,
+            AIRBUS_A321NEO,
+            AIRBUS_A330,
+            AIRBUS_A350,
+            AIRBUS_A380,
+            BOEING_737,
+            BOEING_737_MAX,
+            BOEING_747,
+            BOEING_757,
+            BOEING_767,
+            BOEING_777,
+            BOEING_787,
+            EMBRAER_E175,
+            EMBRAER_E190,
+            BOMBARDIER_CRJ,
+            DASH_8,
+        ] {
+            assert!(db.by_iata(code).is_some(), "{code} is not in the table");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Embedded reference data, so common lookups work without the API.
+
+mod airports;
+mod carriers;
+mod equipment;
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+pub use airports::{AirportDb, AirportInfo, nearby_airports};
+pub use carriers::{CarrierDb, CarrierInfo};
+pub use equipment::{EquipmentDb, EquipmentInfo};
+
+#[derive(Debug, Error)]
+pub enum ReferenceError {
+    #[error("reading {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("line {line}: {reason}")]
+    InvalidRecord { line: usize, reason: String },
+
+    #[error("unknown aircraft family {0:?}")]
+    UnknownFamily(String),
+}
+
+/// Parse a small comma-separated table with a header row. Fields may not
+/// contain commas or quotes.
+fn parse_table(data: &str, columns: usize) -> Result<Vec<(usize, Vec<&str>)>, ReferenceError> {
+    data.lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != columns {
+                return Err(ReferenceError::InvalidRecord {
+                    line: i + 1,
+                    reason: format!("expected {columns} fields, got {}", fields.len()),
+                });
+            }
+            Ok((i + 1, fields))
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+use std::fmt::Write as _;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::model::money::Money;
+use crate::model::response::{Airport, BookingDetails, Segment};
+
+/// GDS-style plain-text itinerary for pasting into email:
+///
+/// ```text
+///  1  AA 1234 O  15FEB  PHXDFW  0600  1040    321
+///          CONNECTION IN DFW  1H15
+///  2  AA  567 O  15FEB  DFWDTW  1155  1555    738
+///
+/// FARE BASIS: OUAHZNB1 OUAHZNB1
+/// TOTAL: USD224.20
+/// ```
+///
+/// Segments are numbered across slices, with a blank line between slices.
+/// Arrivals on a later day carry `+1`, `+2`. Without a booking total, the
+/// pricings are summed for all their passengers.
+pub fn plain_itinerary(details: &BookingDetails) -> String {
+    let mut out = String::new();
+    let slices = details
+        .itinerary
+        .as_ref()
+        .map(|it| it.slices.as_slice())
+        .unwrap_or_default();
+
+    let mut number = 0;
+    for (i, slice) in slices.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for (j, segment) in slice.segments.iter().enumerate() {
+            number += 1;
+            let _ = writeln!(out, "{}", segment_line(number, segment));
+            let is_last = j + 1 == slice.segments.len();
+            if let Some(minutes) = segment.connection.as_ref().and_then(|c| c.duration)
+                && !is_last
+            {
+                let at = segment
+                    .destination
+                    .as_ref()
+                    .and_then(|a| a.code.as_deref())
+                    .unwrap_or("?");
+                let _ = writeln!(
+                    out,
+                    "         CONNECTION IN {at}  {}H{:02}",
+                    minutes / 60,
+                    minutes % 60
+                );
+            }
+        }
+    }
+
+    let mut bases: Vec<&str> = Vec::new();
+    let mut totals: Vec<Money> = Vec::new();
+    for pricing in details.tickets.iter().flat_map(|t| &t.pricings) {
+        bases.extend(pricing.fare_basis_codes());
+        if let Some(total) = pricing.total {
+            let pax = Decimal::from(pricing.pax_count.unwrap_or(1));
+            totals.push(Money::new(total.amount() * pax, total.currency()));
+        }
+    }
+    let total = details
+        .ext
+        .as_ref()
+        .and_then(|e| e.total_price.or(e.price))
+        .or_else(|| Money::try_sum(totals).ok().flatten());
+
+    if !out.is_empty() && (!bases.is_empty() || total.is_some()) {
+        out.push('\n');
+    }
+    if !bases.is_empty() {
+        let _ = writeln!(out, "FARE BASIS: {}", bases.join(" "));
+    }
+    if let Some(total) = total {
+        let _ = writeln!(out, "TOTAL: {total}");
+    }
+    out
+}
+
+fn segment_line(number: usize, segment: &Segment) -> String {
+    let carrier = segment
+        .carrier
+        .as_ref()
+        .and_then(|c| c.code.as_deref())
+        .unwrap_or("??");
+    let flight = segment.flight_number.as_deref().unwrap_or_default();
+    let class = segment
+        .booking_infos
+        .first()
+        .and_then(|b| b.booking_code.as_deref())
+        .unwrap_or(" ");
+    let code = |a: &Option<Airport>| {
+        a.as_ref()
+            .and_then(|a| a.code.clone())
+            .unwrap_or_else(|| "???".to_string())
+    };
+    let departure = segment.departure.as_deref().map(LocalTime::parse);
+    let arrival = segment.arrival.as_deref().map(LocalTime::parse);
+    let date = departure
+        .as_ref()
+        .and_then(|d| d.date)
+        .map(|d| d.format("%d%b").to_string().to_uppercase())
+        .unwrap_or_else(|| "     ".to_string());
+    let days = match (
+        departure.as_ref().and_then(|d| d.date),
+        arrival.as_ref().and_then(|a| a.date),
+    ) {
+        (Some(from), Some(to)) if to > from => format!("+{}", (to - from).num_days()),
+        _ => String::new(),
+    };
+    let equipment = segment
+        .aircraft
+        .as_ref()
+        .and_then(|a| a.code.as_deref())
+        .unwrap_or_default();
+
+    let line = format!(
+        "{number:>2}  {carrier:<2} {flight:>4} {class}  {date}  {}{}  {}  {}{days:<2}  {equipment:>3}",
+        code(&segment.origin),
+        code(&segment.destination),
+        departure.map(|d| d.clock).unwrap_or_default(),
+        arrival.map(|a| a.clock).unwrap_or_default(),
+    );
+    line.trim_end().to_string()
+}
+
+struct LocalTime {
+    date: Option<NaiveDate>,
+    /// `HHMM`, or four spaces when unknown.
+    clock: String,
+}
+
+impl LocalTime {
+    /// From `2026-02-15T06:00-07:00`.
+    fn parse(value: &str) -> Self {
+        let date = value
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let clock = match (value.get(11..13), value.get(14..16)) {
+            (Some(h), Some(m)) => format!("{h}{m}"),
+            _ => "    ".to_string(),
+        };
+        Self { date, clock }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_layout() {
+        let details: BookingDetails = serde_json::from_value(serde_json::json!({
+            "ext": { "totalPrice": "USD224.20" },
+            "itinerary": { "slices": [
+                { "segments": [
+                    {
+                        "carrier": { "code": "AA" }, "flightNumber": "1234",
+                        "origin": { "code": "PHX" }, "destination": { "code": "DFW" },
+                        "departure": "2026-02-15T06:00-07:00",
+                        "arrival": "2026-02-15T10:40-06:00",
+                        "aircraft": { "code": "321" },
+                        "bookingInfos": [{ "bookingCode": "O", "cabin": "COACH" }],
+                        "connection": { "duration": 75 }
+                    },
+                    {
+                        "carrier": { "code": "AA" }, "flightNumber": "567",
+                        "origin": { "code": "DFW" }, "destination": { "code": "DTW" },
+                        "departure": "2026-02-15T11:55-06:00",
+                        "arrival": "2026-02-15T15:55-05:00",
+                        "aircraft": { "code": "738" },
+                        "bookingInfos": [{ "bookingCode": "O" }]
+                    }
+                ]},
+                { "segments": [{
+                    "carrier": { "code": "AA" }, "flightNumber": "88",
+                    "origin": { "code": "DTW" }, "destination": { "code": "PHX" },
+                    "departure": "2026-02-20T23:30-05:00",
+                    "arrival": "2026-02-21T01:10-07:00",
+                    "bookingInfos": [{ "bookingCode": "Q" }]
+                }]}
+            ]},
+            "tickets": [{ "pricings": [{
+                "fares": [{ "code": "OUAHZNB1" }, { "code": "QUA7NBN3" }]
+            }]}]
+        }))
+        .unwrap();
+        assert_eq!(
+            plain_itinerary(&details),
+            " 1  AA 1234 O  15FEB  PHXDFW  0600  1040    321\n\
+             \x20        CONNECTION IN DFW  1H15\n\
+             \x202  AA  567 O  15FEB  DFWDTW  1155  1555    738\n\
+             \n\
+             \x203  AA   88 Q  20FEB  DTWPHX  2330  0110+1\n\
+             \n\
+             FARE BASIS: OUAHZNB1 QUA7NBN3\n\
+             TOTAL: USD224.20\n"
+        );
+    }
+
+    #[test]
+    fn total_falls_back_to_pricings() {
+        let details: BookingDetails = serde_json::from_value(serde_json::json!({
+            "tickets": [
+                { "pricings": [{ "displayPrice": "USD100.00", "paxCount": 2 }] },
+                { "pricings": [{ "displayPrice": "USD50.50" }] }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(plain_itinerary(&details), "TOTAL: USD250.50\n");
+    }
+}
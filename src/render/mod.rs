@@ -0,0 +1,783 @@
+//! Human-readable terminal output for search results and booking details.
+
+mod itinerary;
+mod table;
+
+pub use itinerary::plain_itinerary;
+pub use table::{Align, Table, truncate};
+
+use crate::diff::{PriceDelta, ResultDiff};
+use crate::model::money::Money;
+use crate::model::response::{BookingDetails, CarrierStopMatrix, Segment};
+use crate::model::search::{Solution, SolutionSlice};
+use crate::watch::{Observation, PriceChange, Watch};
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Maximum line width; flexible columns are truncated to fit.
+    pub width: Option<usize>,
+    /// Emit ANSI colors.
+    pub color: bool,
+}
+
+impl RenderOptions {
+    fn paint(&self, text: &str, style: Style) -> String {
+        let code = match style {
+            _ if !self.color || text.is_empty() => return text.to_string(),
+            Style::Plain => return text.to_string(),
+            Style::Header => "1",
+            Style::Highlight => "32",
+            Style::Warning => "33",
+        };
+        format!("\x1b[{code}m{text}\x1b[0m")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    #[default]
+    Plain,
+    Header,
+    /// The cheapest price in a list.
+    Highlight,
+    Warning,
+}
+
+/// One row per slice; price and carriers are shown on the first row of each
+/// solution, and the cheapest price is highlighted.
+pub fn solutions(solutions: &[Solution], options: &RenderOptions) -> String {
+    let mut table = Table::new()
+        .column("#", Align::Right)
+        .column("Price", Align::Right)
+        .flexible("Carriers")
+        .column("Route", Align::Left)
+        .column("Depart", Align::Left)
+        .column("Arrive", Align::Left)
+        .column("Duration", Align::Right)
+        .column("Stops", Align::Left)
+        .flexible("Warnings");
+    // Nothing is highlighted when prices are in more than one currency.
+    let cheapest = Money::try_min(solutions.iter().filter_map(Solution::price))
+        .ok()
+        .flatten();
+
+    for (i, solution) in solutions.iter().enumerate() {
+        let price = solution.price();
+        let price_style = if price.is_some() && price == cheapest {
+            Style::Highlight
+        } else {
+            Style::Plain
+        };
+        let carriers: Vec<&str> = solution
+            .carriers()
+            .into_iter()
+            .filter_map(|c| c.short_name.as_deref().or(c.code.as_deref()))
+            .collect();
+        let lead = vec![
+            ((i + 1).to_string(), Style::Plain),
+            (
+                price.map(|p| p.to_string()).unwrap_or_default(),
+                price_style,
+            ),
+            (carriers.join(", "), Style::Plain),
+        ];
+
+        let slices = solution.slices();
+        if slices.is_empty() {
+            table.styled_row(lead);
+            continue;
+        }
+        for (j, slice) in slices.iter().enumerate() {
+            let lead = if j == 0 {
+                lead.clone()
+            } else {
+                vec![Default::default(); 3]
+            };
+            table.styled_row(lead.into_iter().chain(slice_cells(slice)));
+        }
+    }
+
+    if table.is_empty() {
+        return "No solutions.\n".to_string();
+    }
+    table.render(options)
+}
+
+fn slice_cells(slice: &SolutionSlice) -> Vec<(String, Style)> {
+    let route = format!(
+        "{}-{}",
+        code(slice.origin.as_ref().and_then(|a| a.code.as_deref())),
+        code(slice.destination.as_ref().and_then(|a| a.code.as_deref())),
+    );
+    let stops = match slice.stop_count() {
+        0 => "nonstop".to_string(),
+        n => {
+            let via: Vec<&str> = slice
+                .stops
+                .iter()
+                .filter_map(|a| a.code.as_deref())
+                .collect();
+            if via.is_empty() {
+                n.to_string()
+            } else {
+                format!("{n} ({})", via.join(", "))
+            }
+        }
+    };
+    let warnings = slice
+        .ext
+        .as_ref()
+        .and_then(|e| e.warnings.clone())
+        .unwrap_or_default();
+    vec![
+        (route, Style::Plain),
+        (short_time(slice.departure.as_deref()), Style::Plain),
+        (short_time(slice.arrival.as_deref()), Style::Plain),
+        (duration(slice.duration), Style::Plain),
+        (stops, Style::Plain),
+        (warnings, Style::Warning),
+    ]
+}
+
+/// Each slice as a heading and a table of its segments, then the total.
+pub fn booking_details(details: &BookingDetails, options: &RenderOptions) -> String {
+    let mut out = String::new();
+    let slices = details
+        .itinerary
+        .as_ref()
+        .map(|it| it.slices.as_slice())
+        .unwrap_or_default();
+
+    for (i, slice) in slices.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let heading = format!(
+            "Slice {}: {} to {}, {}",
+            i + 1,
+            code(slice.origin.as_ref().and_then(|a| a.code.as_deref())),
+            code(slice.destination.as_ref().and_then(|a| a.code.as_deref())),
+            duration(slice.duration),
+        );
+        out.push_str(&options.paint(&heading, Style::Header));
+        out.push('\n');
+        if let Some(warnings) = slice.ext.as_ref().and_then(|e| e.warnings.as_deref()) {
+            out.push_str(&options.paint(warnings, Style::Warning));
+            out.push('\n');
+        }
+
+        let mut table = Table::new()
+            .column("Flight", Align::Left)
+            .column("From", Align::Left)
+            .column("To", Align::Left)
+            .column("Depart", Align::Left)
+            .column("Arrive", Align::Left)
+            .column("Duration", Align::Right)
+            .flexible("Aircraft")
+            .column("Class", Align::Left)
+            .column("Connection", Align::Right);
+        for segment in &slice.segments {
+            table.row(segment_cells(segment));
+        }
+        out.push_str(&table.render(options));
+    }
+
+    if let Some(total) = details.ext.as_ref().and_then(|e| e.total_price.or(e.price)) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "Total: {}\n",
+            options.paint(&total.to_string(), Style::Highlight)
+        ));
+    }
+    if out.is_empty() {
+        out.push_str("No booking details.\n");
+    }
+    out
+}
+
+fn segment_cells(segment: &Segment) -> [String; 9] {
+    let flight = format!(
+        "{}{}",
+        segment
+            .carrier
+            .as_ref()
+            .and_then(|c| c.code.as_deref())
+            .unwrap_or_default(),
+        segment.flight_number.as_deref().unwrap_or_default(),
+    );
+    let aircraft = segment
+        .aircraft
+        .as_ref()
+        .and_then(|a| a.name.as_deref().or(a.code.as_deref()))
+        .unwrap_or_default()
+        .to_string();
+    let class: Vec<String> = segment
+        .booking_infos
+        .iter()
+        .map(|b| match (&b.booking_code, &b.cabin) {
+            (Some(code), Some(cabin)) => format!("{code} ({})", cabin.to_lowercase()),
+            (Some(code), None) => code.clone(),
+            (None, Some(cabin)) => cabin.to_lowercase(),
+            (None, None) => String::new(),
+        })
+        .collect();
+    let connection = segment
+        .connection
+        .as_ref()
+        .map(|c| duration(c.duration))
+        .unwrap_or_default();
+    [
+        flight,
+        code(segment.origin.as_ref().and_then(|a| a.code.as_deref())).to_string(),
+        code(segment.destination.as_ref().and_then(|a| a.code.as_deref())).to_string(),
+        short_time(segment.departure.as_deref()),
+        short_time(segment.arrival.as_deref()),
+        duration(segment.duration),
+        aircraft,
+        class.join(", "),
+        connection,
+    ]
+}
+
+/// Carriers down, stop counts across, each cell the lowest price; the
+/// cheapest cell in each column is highlighted.
+pub fn carrier_stop_matrix(matrix: &CarrierStopMatrix, options: &RenderOptions) -> String {
+    let columns = matrix.rows.iter().map(|r| r.stops.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return "No carrier/stop matrix.\n".to_string();
+    }
+
+    let mut table = Table::new().flexible("Carrier");
+    for i in 0..columns {
+        table = table.column(&stop_label(i, columns), Align::Right);
+    }
+    let cheapest: Vec<_> = (0..columns)
+        .map(|i| {
+            Money::try_min(matrix.rows.iter().filter_map(|r| r.stops.get(i)?.min_price))
+                .ok()
+                .flatten()
+        })
+        .collect();
+
+    for row in &matrix.rows {
+        let carrier = row
+            .carrier
+            .as_ref()
+            .and_then(|c| c.short_name.as_deref().or(c.code.as_deref()))
+            .unwrap_or("?")
+            .to_string();
+        let cells = row.stops.iter().zip(&cheapest).map(|(cell, cheapest)| {
+            let style = if cell.min_price.is_some() && cell.min_price == *cheapest {
+                Style::Highlight
+            } else {
+                Style::Plain
+            };
+            (
+                cell.min_price.map(|p| p.to_string()).unwrap_or_default(),
+                style,
+            )
+        });
+        table.styled_row([(carrier, Style::Plain)].into_iter().chain(cells));
+    }
+    table.render(options)
+}
+
+/// The last column collects every higher stop count, as the API does.
+fn stop_label(index: usize, columns: usize) -> String {
+    match index {
+        0 => "Nonstop".to_string(),
+        1 if columns > 2 => "1 stop".to_string(),
+        1 => "1+ stops".to_string(),
+        n if n + 1 == columns => format!("{n}+ stops"),
+        n => format!("{n} stops"),
+    }
+}
+
+/// The cheapest-price change, then one row per itinerary that was
+/// repriced, appeared or disappeared. Drops are highlighted, rises flagged.
+pub fn result_diff(diff: &ResultDiff, options: &RenderOptions) -> String {
+    let mut out = String::new();
+    let price = |p: Option<Money>| p.map(|p| p.to_string()).unwrap_or_else(|| "none".into());
+    let cheapest = &diff.cheapest;
+    out.push_str(&format!(
+        "Cheapest: {} -> {}",
+        price(cheapest.before),
+        price(cheapest.after)
+    ));
+    let (change, style) = delta_cell(cheapest);
+    if !change.is_empty() {
+        out.push_str(&format!(", {}", options.paint(&change, style)));
+    }
+    out.push('\n');
+
+    let mut table = Table::new()
+        .column("Change", Align::Left)
+        .flexible("Itinerary")
+        .column("Before", Align::Right)
+        .column("After", Align::Right)
+        .column("Difference", Align::Right);
+    let cell = |p: Option<Money>| (p.map(|p| p.to_string()).unwrap_or_default(), Style::Plain);
+    for repriced in &diff.repriced {
+        let delta = &repriced.price;
+        let label = match delta.amount.map(|a| a.amount().is_sign_negative()) {
+            Some(true) => "cheaper",
+            Some(false) => "pricier",
+            None => "repriced",
+        };
+        table.styled_row([
+            (label.to_string(), Style::Plain),
+            (repriced.signature.to_string(), Style::Plain),
+            cell(delta.before),
+            cell(delta.after),
+            delta_cell(delta),
+        ]);
+    }
+    for itinerary in &diff.appeared {
+        table.styled_row([
+            ("new".to_string(), Style::Plain),
+            (itinerary.signature.to_string(), Style::Plain),
+            cell(None),
+            cell(itinerary.price),
+            cell(None),
+        ]);
+    }
+    for itinerary in &diff.disappeared {
+        table.styled_row([
+            ("gone".to_string(), Style::Plain),
+            (itinerary.signature.to_string(), Style::Plain),
+            cell(itinerary.price),
+            cell(None),
+            cell(None),
+        ]);
+    }
+    if !table.is_empty() {
+        out.push('\n');
+        out.push_str(&table.render(options));
+    }
+
+    let mut notes = vec![format!("{} unchanged", diff.unchanged)];
+    if diff.unmatched > 0 {
+        notes.push(format!("{} without flight details", diff.unmatched));
+    }
+    out.push_str(&format!("\n{}.\n", notes.join(", ")));
+    out
+}
+
+/// `-20.00 (-8.00%)`, highlighted for a drop.
+fn delta_cell(delta: &PriceDelta) -> (String, Style) {
+    let Some(amount) = delta.amount else {
+        return (String::new(), Style::Plain);
+    };
+    let amount = amount.amount();
+    let mut text = format!("{amount:+.2}");
+    if let Some(percent) = delta.percent {
+        text.push_str(&format!(" ({percent:+.2}%)"));
+    }
+    let style = if amount.is_sign_negative() && !amount.is_zero() {
+        Style::Highlight
+    } else if amount.is_zero() {
+        Style::Plain
+    } else {
+        Style::Warning
+    };
+    (text, style)
+}
+
+/// One row per watch with its latest price and the change from the run
+/// before; new lows are highlighted.
+pub fn watches(watches: &[(&Watch, &[Observation])], options: &RenderOptions) -> String {
+    let mut table = Table::new()
+        .flexible("Watch")
+        .column("Every", Align::Right)
+        .column("Last run (UTC)", Align::Left)
+        .column("Cheapest", Align::Right)
+        .column("Change", Align::Right);
+    for (watch, history) in watches {
+        let change = PriceChange::from_history(history);
+        let last = history.last();
+        table.styled_row([
+            (watch.name.clone(), Style::Plain),
+            (interval(watch.interval_secs), Style::Plain),
+            (
+                last.map(|o| o.at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default(),
+                Style::Plain,
+            ),
+            price_cell(last, change.as_ref()),
+            (change_label(change.as_ref()), Style::Plain),
+        ]);
+    }
+    if table.is_empty() {
+        return "No watches.\n".to_string();
+    }
+    table.render(options)
+}
+
+/// One row per run, oldest first, each compared with the runs before it.
+pub fn price_history(history: &[Observation], options: &RenderOptions) -> String {
+    let mut table = Table::new()
+        .column("Time (UTC)", Align::Left)
+        .column("Cheapest", Align::Right)
+        .column("Change", Align::Right)
+        .column("Solutions", Align::Right);
+    for (i, observation) in history.iter().enumerate() {
+        let change = PriceChange::from_history(&history[..=i]);
+        table.styled_row([
+            (
+                observation.at.format("%Y-%m-%d %H:%M").to_string(),
+                Style::Plain,
+            ),
+            price_cell(Some(observation), change.as_ref()),
+            (change_label(change.as_ref()), Style::Plain),
+            (observation.solution_count.to_string(), Style::Plain),
+        ]);
+    }
+    if table.is_empty() {
+        return "No history.\n".to_string();
+    }
+    table.render(options)
+}
+
+fn price_cell(observation: Option<&Observation>, change: Option<&PriceChange>) -> (String, Style) {
+    let price = observation
+        .and_then(|o| o.cheapest)
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    let style = if change.is_some_and(|c| c.new_low) {
+        Style::Highlight
+    } else {
+        Style::Plain
+    };
+    (price, style)
+}
+
+/// `+6.67%`, `-25.00%, new low`.
+fn change_label(change: Option<&PriceChange>) -> String {
+    let Some(change) = change else {
+        return String::new();
+    };
+    let percent = change.percent.map(|p| {
+        let sign = if p.is_sign_positive() && !p.is_zero() {
+            "+"
+        } else {
+            ""
+        };
+        format!("{sign}{p:.2}%")
+    });
+    match (percent, change.new_low) {
+        (Some(p), true) => format!("{p}, new low"),
+        (Some(p), false) => p,
+        (None, true) => "new low".to_string(),
+        (None, false) => String::new(),
+    }
+}
+
+/// Seconds as `30m`, `6h` or `1d 12h`.
+fn interval(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")]
+        .into_iter()
+        .filter(|&(n, _)| n > 0)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        format!("{secs}s")
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn code(code: Option<&str>) -> &str {
+    code.unwrap_or("?")
+}
+
+/// `2025-03-01T22:30-08:00` as `03-01 22:30`; anything else unchanged.
+fn short_time(time: Option<&str>) -> String {
+    let Some(time) = time else {
+        return String::new();
+    };
+    match (time.get(5..10), time.get(10..11), time.get(11..16)) {
+        (Some(date), Some("T"), Some(clock)) => format!("{date} {clock}"),
+        _ => time.to_string(),
+    }
+}
+
+/// Minutes as `5h 20m`.
+fn duration(minutes: Option<u32>) -> String {
+    match minutes {
+        Some(m) if m >= 60 => format!("{}h {:02}m", m / 60, m % 60),
+        Some(m) => format!("{m}m"),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain() -> RenderOptions {
+        RenderOptions::default()
+    }
+
+    fn solution(id: &str, price: &str, warnings: Option<&str>) -> Solution {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "displayTotal": price,
+            "itinerary": {
+                "carriers": [{ "code": "AA", "shortName": "American" }],
+                "slices": [
+                    {
+                        "origin": { "code": "PHX" },
+                        "destination": { "code": "DTW" },
+                        "departure": "2026-02-15T06:00-07:00",
+                        "arrival": "2026-02-15T15:55-05:00",
+                        "duration": 415,
+                        "flights": ["AA1234", "AA567"],
+                        "stops": [{ "code": "DFW" }],
+                        "ext": { "warnings": warnings }
+                    },
+                    {
+                        "origin": { "code": "DTW" },
+                        "destination": { "code": "PHX" },
+                        "departure": "2026-02-20T18:00-05:00",
+                        "arrival": "2026-02-20T20:10-07:00",
+                        "duration": 310,
+                        "flights": ["AA99"]
+                    }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn solution_rows_per_slice() {
+        let out = solutions(
+            &[
+                solution("a", "USD300.00", Some("Long layover")),
+                solution("b", "USD224.20", None),
+            ],
+            &plain(),
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 6, "{out}");
+        assert!(lines[0].starts_with("#      Price  Carriers"));
+        assert!(lines[2].contains("USD300.00  American  PHX-DTW  02-15 06:00  02-15 15:55"));
+        assert!(lines[2].contains("6h 55m  1 (DFW)  Long layover"));
+        assert!(lines[3].starts_with("                        DTW-PHX"));
+        assert!(lines[3].contains("nonstop"));
+        assert!(!out.contains('\x1b'));
+    }
+
+    #[test]
+    fn color_highlights_cheapest_and_warnings() {
+        let options = RenderOptions {
+            width: None,
+            color: true,
+        };
+        let out = solutions(
+            &[
+                solution("a", "USD300.00", Some("Long layover")),
+                solution("b", "USD224.20", None),
+            ],
+            &options,
+        );
+        assert!(out.contains("\x1b[32mUSD224.20\x1b[0m"));
+        assert!(!out.contains("\x1b[32mUSD300.00"));
+        assert!(out.contains("\x1b[33mLong layover\x1b[0m"));
+        assert!(out.starts_with("\x1b[1m#\x1b[0m"));
+    }
+
+    #[test]
+    fn mixed_currencies_highlight_nothing() {
+        let options = RenderOptions {
+            width: None,
+            color: true,
+        };
+        let out = solutions(
+            &[
+                solution("a", "EUR900.00", None),
+                solution("b", "USD100.00", None),
+            ],
+            &options,
+        );
+        assert!(!out.contains("\x1b[32m"), "{out}");
+
+        let matrix: CarrierStopMatrix = serde_json::from_value(serde_json::json!({
+            "rows": [
+                { "carrier": { "code": "AA" }, "stops": [{ "minPrice": "EUR900.00" }] },
+                { "carrier": { "code": "DL" }, "stops": [{ "minPrice": "USD100.00" }] }
+            ]
+        }))
+        .unwrap();
+        assert!(!carrier_stop_matrix(&matrix, &options).contains("\x1b[32m"));
+    }
+
+    #[test]
+    fn narrow_width_truncates_warnings() {
+        let options = RenderOptions {
+            width: Some(90),
+            color: false,
+        };
+        let warning = "Long layover; change of airport; overnight flight";
+        let out = solutions(&[solution("a", "USD300.00", Some(warning))], &options);
+        assert!(
+            out.lines()
+                .all(|l| unicode_width::UnicodeWidthStr::width(l) <= 90),
+            "{out}"
+        );
+        assert!(out.contains('…'));
+    }
+
+    #[test]
+    fn booking_details_segments_and_total() {
+        let details: BookingDetails = serde_json::from_value(serde_json::json!({
+            "ext": { "totalPrice": "USD224.20" },
+            "itinerary": { "slices": [{
+                "origin": { "code": "PHX" },
+                "destination": { "code": "DTW" },
+                "duration": 415,
+                "segments": [
+                    {
+                        "carrier": { "code": "AA" },
+                        "flightNumber": "1234",
+                        "origin": { "code": "PHX" },
+                        "destination": { "code": "DFW" },
+                        "departure": "2026-02-15T06:00-07:00",
+                        "arrival": "2026-02-15T10:40-05:00",
+                        "duration": 160,
+                        "aircraft": { "code": "321", "name": "Airbus A321" },
+                        "bookingInfos": [{ "bookingCode": "O", "cabin": "COACH" }],
+                        "connection": { "duration": 75 }
+                    },
+                    {
+                        "carrier": { "code": "AA" },
+                        "flightNumber": "567",
+                        "origin": { "code": "DFW" },
+                        "destination": { "code": "DTW" }
+                    }
+                ]
+            }]}
+        }))
+        .unwrap();
+        let out = booking_details(&details, &plain());
+        assert!(out.starts_with("Slice 1: PHX to DTW, 6h 55m\n"));
+        assert!(out.contains("AA1234  PHX   DFW  02-15 06:00  02-15 10:40"));
+        assert!(out.contains("Airbus A321  O (coach)"));
+        assert!(out.contains("1h 15m"));
+        assert!(out.contains("AA567"));
+        assert!(out.ends_with("\nTotal: USD224.20\n"));
+    }
+
+    #[test]
+    fn matrix_grid() {
+        let matrix: CarrierStopMatrix = serde_json::from_value(serde_json::json!({
+            "rows": [
+                { "carrier": { "code": "AA" }, "stops": [
+                    { "minPrice": "USD300.00" }, { "minPrice": "USD210.00" }, {}
+                ]},
+                { "carrier": { "code": "DL", "shortName": "Delta" }, "stops": [
+                    { "minPrice": "USD280.00" }, { "minPrice": "USD250.00" },
+                    { "minPrice": "USD199.00" }
+                ]}
+            ]
+        }))
+        .unwrap();
+        let out = carrier_stop_matrix(&matrix, &plain());
+        assert_eq!(
+            out.lines().next(),
+            Some("Carrier    Nonstop     1 stop   2+ stops")
+        );
+        assert!(out.contains("AA       USD300.00  USD210.00\n"));
+        assert!(out.contains("Delta    USD280.00  USD250.00  USD199.00"));
+
+        let colored = carrier_stop_matrix(
+            &matrix,
+            &RenderOptions {
+                width: None,
+                color: true,
+            },
+        );
+        assert!(colored.contains("\x1b[32mUSD280.00\x1b[0m"));
+        assert!(colored.contains("\x1b[32mUSD210.00\x1b[0m"));
+    }
+
+    #[test]
+    fn formats_times_and_durations() {
+        assert_eq!(short_time(Some("2025-03-01T22:30-08:00")), "03-01 22:30");
+        assert_eq!(short_time(Some("22:30")), "22:30");
+        assert_eq!(duration(Some(320)), "5h 20m");
+        assert_eq!(duration(Some(45)), "45m");
+        assert_eq!(stop_label(1, 2), "1+ stops");
+        assert_eq!(interval(6 * 3600), "6h");
+        assert_eq!(interval(36 * 3600 + 300), "1d 12h 5m");
+    }
+
+    #[test]
+    fn result_diff_rows() {
+        let solution = |id: &str, price: &str, flight: &str| -> Solution {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "displayTotal": price,
+                "itinerary": { "slices": [{
+                    "departure": "2026-02-15T06:00-07:00",
+                    "flights": [flight]
+                }]}
+            }))
+            .unwrap()
+        };
+        let before = [
+            solution("a", "USD250.00", "DL2"),
+            solution("b", "USD400.00", "UA3"),
+        ];
+        let after = [
+            solution("c", "USD230.00", "DL2"),
+            solution("d", "USD199.00", "B64"),
+        ];
+        let out = result_diff(&crate::diff::diff_solutions(&before, &after), &plain());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Cheapest: USD250.00 -> USD199.00, -51.00 (-20.40%)"
+        );
+        assert!(lines[4].starts_with("cheaper  DL2/2026-02-15  USD250.00  USD230.00"));
+        assert!(lines[4].ends_with("-20.00 (-8.00%)"));
+        assert!(lines[5].starts_with("new      B64/2026-02-15"));
+        assert!(lines[6].starts_with("gone     UA3/2026-02-15  USD400.00"));
+        assert_eq!(lines.last(), Some(&"0 unchanged."));
+    }
+
+    #[test]
+    fn price_history_changes() {
+        let observed = |hour: u32, price: &str| Observation {
+            at: chrono::DateTime::parse_from_rfc3339(&format!("2026-10-01T{hour:02}:00:00Z"))
+                .unwrap()
+                .to_utc(),
+            cheapest: Some(price.parse().unwrap()),
+            solution_count: 12,
+            solutions: Vec::new(),
+        };
+        let history = [
+            observed(6, "USD200.00"),
+            observed(12, "USD150.00"),
+            observed(18, "USD160.00"),
+        ];
+        let out = price_history(&history, &plain());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[2],
+            "2026-10-01 06:00  USD200.00                           12"
+        );
+        assert_eq!(
+            lines[3],
+            "2026-10-01 12:00  USD150.00  -25.00%, new low         12"
+        );
+        assert_eq!(
+            lines[4],
+            "2026-10-01 18:00  USD160.00            +6.67%         12"
+        );
+        assert_eq!(price_history(&[], &plain()), "No history.\n");
+    }
+}
@@ -0,0 +1,231 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::{RenderOptions, Style};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    title: String,
+    align: Align,
+    /// Whether the column may be truncated to fit the width.
+    flexible: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Cell {
+    text: String,
+    style: Style,
+}
+
+/// A plain-text table with aligned columns.
+///
+/// Columns marked flexible shrink, widest first, until the table fits
+/// [`RenderOptions::width`]; truncated cells end in `…`.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+}
+
+const GAP: &str = "  ";
+const MIN_FLEX_WIDTH: usize = 4;
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn column(mut self, title: &str, align: Align) -> Self {
+        self.columns.push(Column {
+            title: title.to_string(),
+            align,
+            flexible: false,
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn flexible(mut self, title: &str) -> Self {
+        self.columns.push(Column {
+            title: title.to_string(),
+            align: Align::Left,
+            flexible: true,
+        });
+        self
+    }
+
+    pub fn row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.styled_row(cells.into_iter().map(|c| (c.into(), Style::Plain)));
+    }
+
+    pub fn styled_row(&mut self, cells: impl IntoIterator<Item = (String, Style)>) {
+        let mut row: Vec<Cell> = cells
+            .into_iter()
+            .map(|(text, style)| Cell { text, style })
+            .collect();
+        assert!(
+            row.len() <= self.columns.len(),
+            "row has {} cells for {} columns",
+            row.len(),
+            self.columns.len()
+        );
+        row.resize_with(self.columns.len(), Cell::default);
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let widths = self.widths(options.width);
+        let mut out = String::new();
+
+        let header = self.columns.iter().map(|c| Cell {
+            text: c.title.clone(),
+            style: Style::Header,
+        });
+        self.render_row(&mut out, header, &widths, options);
+        let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+        out.push_str(&rule.join(GAP));
+        out.push('\n');
+
+        for row in &self.rows {
+            self.render_row(&mut out, row.iter().cloned(), &widths, options);
+        }
+        out
+    }
+
+    fn render_row(
+        &self,
+        out: &mut String,
+        cells: impl Iterator<Item = Cell>,
+        widths: &[usize],
+        options: &RenderOptions,
+    ) {
+        let mut line = String::new();
+        for (i, (cell, column)) in cells.zip(&self.columns).enumerate() {
+            if i > 0 {
+                line.push_str(GAP);
+            }
+            let text = truncate(&cell.text, widths[i]);
+            let pad = " ".repeat(widths[i] - text.width());
+            let text = options.paint(&text, cell.style);
+            match column.align {
+                Align::Left => {
+                    line.push_str(&text);
+                    line.push_str(&pad);
+                }
+                Align::Right => {
+                    line.push_str(&pad);
+                    line.push_str(&text);
+                }
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    fn widths(&self, max: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.columns.iter().map(|c| c.title.width()).collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(cell.text.width());
+            }
+        }
+
+        let Some(max) = max else {
+            return widths;
+        };
+        let gaps = GAP.len() * self.columns.len().saturating_sub(1);
+        while widths.iter().sum::<usize>() + gaps > max {
+            let widest = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .filter(|&(_, (c, &w))| c.flexible && w > MIN_FLEX_WIDTH)
+                .max_by_key(|&(_, (_, &w))| w)
+                .map(|(i, _)| i);
+            match widest {
+                Some(i) => widths[i] -= 1,
+                None => break,
+            }
+        }
+        widths
+    }
+}
+
+/// Cut `text` to `width` display columns, ending in `…` when cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(width: Option<usize>) -> RenderOptions {
+        RenderOptions {
+            width,
+            color: false,
+        }
+    }
+
+    #[test]
+    fn aligns_columns() {
+        let mut table = Table::new()
+            .column("Price", Align::Right)
+            .flexible("Carriers");
+        table.row(["USD99.00", "AA"]);
+        table.row(["USD1,234.00", "United Airlines"]);
+        assert_eq!(
+            table.render(&plain(None)),
+            "      Price  Carriers\n\
+             ───────────  ───────────────\n\
+             \u{20}  USD99.00  AA\n\
+             USD1,234.00  United Airlines\n"
+        );
+    }
+
+    #[test]
+    fn shrinks_flexible_columns_to_fit() {
+        let mut table = Table::new().column("Id", Align::Left).flexible("Note");
+        table.row(["1", "a rather long warning about the itinerary"]);
+        let out = table.render(&plain(Some(20)));
+        assert!(out.lines().all(|l| l.width() <= 20), "{out}");
+        assert!(out.contains("a rather long w…"));
+    }
+
+    #[test]
+    fn truncates_wide_characters() {
+        assert_eq!(truncate("東京羽田空港", 7), "東京羽…");
+        assert_eq!(truncate("short", 10), "short");
+    }
+}
@@ -0,0 +1,586 @@
+//! Saved search definitions, loaded from TOML or JSON and turned into
+//! [`SearchRequest`]s.
+//!
+//! ```toml
+//! [[search]]
+//! name = "bos-lax-weekend"
+//! cabin = "premium-coach"
+//! pax = { adults = 2, children = 1 }
+//! routing = "C:UA+"
+//! sort = "price"
+//! filter = { max_price = 900.0 }
+//!
+//! [[search.slices]]
+//! from = ["BOS", "PVD"]
+//! to = "LAX"
+//! date = "next-fri"
+//!
+//! [[search.slices]]
+//! from = "LAX"
+//! to = ["BOS", "PVD"]
+//! date = "+10d"
+//! extension = "-REDEYES"
+//! ```
+//!
+//! Dates are `YYYY-MM-DD`, `today`, `tomorrow`, an offset from today
+//! (`+30d`, `+2w`, `+1m`) or the next weekday after today (`next-fri`).
+
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::model::common::{Cabin, CarrierFilter, Pax, PriceFilter, SearchFilter};
+use crate::model::routing::{ExtensionCode, ParseError, RoutingCode};
+use crate::model::search::SearchRequest;
+
+#[derive(Debug, Error)]
+pub enum SavedSearchError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{0}: expected a .toml or .json file")]
+    UnknownFormat(PathBuf),
+
+    #[error("invalid date {value:?}: {reason}")]
+    InvalidDate { value: String, reason: String },
+
+    #[error("more than one search is named {0:?}")]
+    DuplicateName(String),
+
+    #[error("search {search:?} has no slices")]
+    NoSlices { search: String },
+
+    #[error("search {search:?}: invalid routing code: {source}")]
+    Routing {
+        search: String,
+        #[source]
+        source: ParseError,
+    },
+
+    #[error("search {search:?}: invalid extension code: {source}")]
+    Extension {
+        search: String,
+        #[source]
+        source: ParseError,
+    },
+}
+
+/// A file of saved searches, one `[[search]]` table each.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedSearches {
+    #[serde(default, rename = "search")]
+    pub searches: Vec<SavedSearch>,
+}
+
+impl SavedSearches {
+    /// Load a `.toml` or `.json` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SavedSearchError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| SavedSearchError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml(&data),
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&data),
+            _ => Err(SavedSearchError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_toml(data: &str) -> Result<Self, SavedSearchError> {
+        toml::from_str::<Self>(data)?.unique_names()
+    }
+
+    pub fn from_json(data: &str) -> Result<Self, SavedSearchError> {
+        serde_json::from_str::<Self>(data)?.unique_names()
+    }
+
+    /// Names are how searches are picked and watched, so each must be unique.
+    fn unique_names(self) -> Result<Self, SavedSearchError> {
+        for (i, search) in self.searches.iter().enumerate() {
+            if self.searches[..i].iter().any(|s| s.name == search.name) {
+                return Err(SavedSearchError::DuplicateName(search.name.clone()));
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.iter().find(|s| s.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearch {
+    pub name: String,
+    pub slices: Vec<SavedSlice>,
+    pub pax: Pax,
+    #[serde(
+        deserialize_with = "lenient_cabin",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cabin: Option<Cabin>,
+    /// Routing code for every slice without its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing: Option<String>,
+    /// Extension code for every slice without its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// Maximum extra stops relative to the fewest available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stops: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_of_airport: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_availability: Option<bool>,
+    pub filter: SavedFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSlice {
+    #[serde(deserialize_with = "codes")]
+    pub from: Vec<String>,
+    #[serde(deserialize_with = "codes")]
+    pub to: Vec<String>,
+    pub date: DateSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedFilter {
+    /// Only these marketing carriers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub carriers: Vec<String>,
+    /// Absolute stop limit per slice.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stop_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<f64>,
+}
+
+impl From<&SavedFilter> for SearchFilter {
+    fn from(filter: &SavedFilter) -> Self {
+        let price =
+            (filter.min_price.is_some() || filter.max_price.is_some()).then_some(PriceFilter {
+                min: filter.min_price,
+                max: filter.max_price,
+            });
+        Self {
+            carriers: (!filter.carriers.is_empty()).then(|| CarrierFilter {
+                values: filter.carriers.clone(),
+            }),
+            max_stop_count: filter.max_stop_count,
+            overnight: None,
+            price,
+        }
+    }
+}
+
+impl SavedSearch {
+    /// The request this search stands for, with relative dates resolved
+    /// against `today`.
+    pub fn to_request(&self, today: NaiveDate) -> Result<SearchRequest, SavedSearchError> {
+        if self.slices.is_empty() {
+            return Err(SavedSearchError::NoSlices {
+                search: self.name.clone(),
+            });
+        }
+
+        let mut builder = SearchRequest::builder()
+            .pax(self.pax.clone())
+            .filter((&self.filter).into());
+        for slice in &self.slices {
+            let from: Vec<&str> = slice.from.iter().map(String::as_str).collect();
+            let to: Vec<&str> = slice.to.iter().map(String::as_str).collect();
+            let date = slice
+                .date
+                .resolve(today)
+                .ok_or_else(|| SavedSearchError::InvalidDate {
+                    value: slice.date.to_string(),
+                    reason: format!("out of range from {today}"),
+                })?
+                .to_string();
+            builder = builder.add_slice(&from, &to, &date);
+            if let Some(code) = &slice.routing {
+                builder = builder.slice_routing(self.routing_code(code)?);
+            }
+            if let Some(code) = &slice.extension {
+                builder = builder.slice_extension(self.extension_code(code)?);
+            }
+        }
+        if let Some(code) = &self.routing {
+            builder = builder.routing(self.routing_code(code)?);
+        }
+        if let Some(code) = &self.extension {
+            builder = builder.extension(self.extension_code(code)?);
+        }
+        if let Some(cabin) = self.cabin {
+            builder = builder.cabin(cabin);
+        }
+        if let Some(n) = self.max_stops {
+            builder = builder.max_stops(n);
+        }
+        if let Some(sort) = &self.sort {
+            builder = builder.sort(sort);
+        }
+        if let Some(size) = self.page_size {
+            builder = builder.page_size(size);
+        }
+        if let Some(allow) = self.change_of_airport {
+            builder = builder.change_of_airport(allow);
+        }
+        if let Some(check) = self.check_availability {
+            builder = builder.check_availability(check);
+        }
+        Ok(builder.build())
+    }
+
+    fn routing_code(&self, code: &str) -> Result<RoutingCode, SavedSearchError> {
+        code.parse().map_err(|source| SavedSearchError::Routing {
+            search: self.name.clone(),
+            source,
+        })
+    }
+
+    fn extension_code(&self, code: &str) -> Result<ExtensionCode, SavedSearchError> {
+        code.parse().map_err(|source| SavedSearchError::Extension {
+            search: self.name.clone(),
+            source,
+        })
+    }
+}
+
+/// A departure date, fixed or relative to the day the search runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSpec {
+    Fixed(NaiveDate),
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    /// The first such weekday after today.
+    Next(Weekday),
+}
+
+impl DateSpec {
+    /// The date this spec names, counting from `today`. `None` if it falls
+    /// outside the representable range.
+    pub fn resolve(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Fixed(date) => Some(date),
+            Self::Days(n) => today.checked_add_days(Days::new(n.into())),
+            Self::Weeks(n) => today.checked_add_days(Days::new(u64::from(n) * 7)),
+            Self::Months(n) => today.checked_add_months(Months::new(n)),
+            Self::Next(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 7
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                let ahead = if ahead == 0 { 7 } else { ahead };
+                today.checked_add_days(Days::new(ahead.into()))
+            }
+        }
+    }
+}
+
+impl FromStr for DateSpec {
+    type Err = SavedSearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| SavedSearchError::InvalidDate {
+            value: s.to_string(),
+            reason: reason.to_string(),
+        };
+        let value = s.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "today" => return Ok(Self::Days(0)),
+            "tomorrow" => return Ok(Self::Days(1)),
+            _ => {}
+        }
+        if let Some(offset) = value.strip_prefix('+') {
+            let unit_at = offset
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(|| invalid("expected a unit: d, w or m"))?;
+            let (count, unit) = offset.split_at(unit_at);
+            let count: u32 = count.parse().map_err(|_| invalid("expected a number"))?;
+            return match unit {
+                "d" => Ok(Self::Days(count)),
+                "w" => Ok(Self::Weeks(count)),
+                "m" => Ok(Self::Months(count)),
+                _ => Err(invalid("expected a unit: d, w or m")),
+            };
+        }
+        if let Some(day) = value.strip_prefix("next-") {
+            return day
+                .parse()
+                .map(Self::Next)
+                .map_err(|_| invalid("expected a weekday, e.g. next-fri"));
+        }
+        NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+            .map(Self::Fixed)
+            .map_err(|_| invalid("expected YYYY-MM-DD, +30d, +2w, +1m or next-fri"))
+    }
+}
+
+impl Display for DateSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(date) => write!(f, "{date}"),
+            Self::Days(0) => f.write_str("today"),
+            Self::Days(n) => write!(f, "+{n}d"),
+            Self::Weeks(n) => write!(f, "+{n}w"),
+            Self::Months(n) => write!(f, "+{n}m"),
+            Self::Next(day) => write!(f, "next-{}", day.to_string().to_ascii_lowercase()),
+        }
+    }
+}
+
+impl Serialize for DateSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// `"BOS"`, `"BOS,PVD"` or `["BOS", "PVD"]`, upper-cased.
+fn codes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Codes {
+        One(String),
+        Many(Vec<String>),
+    }
+    let list = match Codes::deserialize(deserializer)? {
+        Codes::One(s) => s.split(',').map(str::to_string).collect(),
+        Codes::Many(list) => list,
+    };
+    let codes: Vec<String> = list
+        .iter()
+        .map(|c| c.trim().to_ascii_uppercase())
+        .filter(|c| !c.is_empty())
+        .collect();
+    if codes.is_empty() {
+        return Err(serde::de::Error::custom(
+            "expected at least one airport code",
+        ));
+    }
+    Ok(codes)
+}
+
+/// `coach`, `Premium Coach`, `premium-economy`, `BUSINESS`, ...
+fn lenient_cabin<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cabin>, D::Error> {
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let key: String = s
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match key.as_str() {
+        "coach" | "economy" => Ok(Some(Cabin::Coach)),
+        "premiumcoach" | "premiumeconomy" => Ok(Some(Cabin::PremiumCoach)),
+        "business" => Ok(Some(Cabin::Business)),
+        "first" => Ok(Some(Cabin::First)),
+        _ => Err(serde::de::Error::custom(format!("unknown cabin {s:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+        [[search]]
+        name = "bos-lax"
+        cabin = "premium-coach"
+        pax = { adults = 2, children = 1 }
+        routing = "C:UA+"
+        sort = "price"
+        filter = { max_price = 900.0, carriers = ["UA"] }
+
+        [[search.slices]]
+        from = ["bos", "PVD"]
+        to = "LAX"
+        date = "next-fri"
+
+        [[search.slices]]
+        from = "LAX"
+        to = "BOS,PVD"
+        date = "+10d"
+        routing = "C:AA+"
+        extension = "-REDEYES"
+
+        [[search]]
+        name = "fixed"
+        slices = [{ from = "SFO", to = "NRT", date = "2026-12-20" }]
+    "#;
+
+    fn today() -> NaiveDate {
+        // A Wednesday.
+        NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+    }
+
+    #[test]
+    fn resolves_relative_dates() {
+        let resolve = |s: &str| {
+            s.parse::<DateSpec>()
+                .unwrap()
+                .resolve(today())
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(resolve("today"), "2026-10-14");
+        assert_eq!(resolve("+30d"), "2026-11-13");
+        assert_eq!(resolve("+2w"), "2026-10-28");
+        assert_eq!(resolve("+1m"), "2026-11-14");
+        assert_eq!(resolve("next-fri"), "2026-10-16");
+        assert_eq!(resolve("next-wednesday"), "2026-10-21");
+        assert_eq!(resolve("2027-01-05"), "2027-01-05");
+        assert!("+3y".parse::<DateSpec>().is_err());
+        assert!("next-holiday".parse::<DateSpec>().is_err());
+        assert_eq!(
+            "NEXT-FRI".parse::<DateSpec>().unwrap().to_string(),
+            "next-fri"
+        );
+    }
+
+    #[test]
+    fn toml_to_request() {
+        let saved = SavedSearches::from_toml(TOML).unwrap();
+        assert_eq!(saved.searches.len(), 2);
+        let request = saved.get("bos-lax").unwrap().to_request(today()).unwrap();
+        let inputs = &request.inputs;
+
+        assert_eq!(inputs.cabin, Cabin::PremiumCoach);
+        assert_eq!((inputs.pax.adults, inputs.pax.children), (2, 1));
+        assert_eq!(inputs.sorts, "price");
+        assert_eq!(inputs.filter.price.as_ref().unwrap().max, Some(900.0));
+        assert_eq!(inputs.filter.carriers.as_ref().unwrap().values, ["UA"]);
+
+        let [out, back] = &inputs.slices[..] else {
+            panic!("expected two slices");
+        };
+        assert_eq!(out.origins, ["BOS", "PVD"]);
+        assert_eq!(out.date, "2026-10-16");
+        assert_eq!(out.route_language.as_deref(), Some("C:UA+"));
+        assert_eq!(out.command_line, None);
+        assert_eq!(back.destinations, ["BOS", "PVD"]);
+        assert_eq!(back.date, "2026-10-24");
+        assert_eq!(back.route_language.as_deref(), Some("C:AA+"));
+        assert_eq!(back.command_line.as_deref(), Some("-REDEYES"));
+    }
+
+    #[test]
+    fn json_matches_toml() {
+        let json = r#"{ "search": [{
+            "name": "fixed",
+            "slices": [{ "from": "SFO", "to": ["NRT"], "date": "2026-12-20" }]
+        }]}"#;
+        let saved = SavedSearches::from_json(json).unwrap();
+        let request = saved.searches[0].to_request(today()).unwrap();
+        assert_eq!(request.inputs.pax.adults, 1);
+        assert_eq!(request.inputs.cabin, Cabin::Coach);
+        assert_eq!(request.inputs.slices[0].date, "2026-12-20");
+    }
+
+    #[test]
+    fn loads_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trips.toml");
+        fs::write(&path, TOML).unwrap();
+        assert_eq!(SavedSearches::load(&path).unwrap().searches.len(), 2);
+
+        let other = dir.path().join("trips.yaml");
+        fs::write(&other, "").unwrap();
+        assert!(matches!(
+            SavedSearches::load(&other),
+            Err(SavedSearchError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn reports_bad_definitions() {
+        let bad_date = "[[search]]\nslices = [{ from = \"A\", to = \"B\", date = \"soon\" }]";
+        let err = SavedSearches::from_toml(bad_date).unwrap_err();
+        assert!(err.to_string().contains("soon"), "{err}");
+
+        let bad_routing = "[[search]]\nname = \"x\"\nrouting = \"C:\"\n\
+                           slices = [{ from = \"A\", to = \"B\", date = \"today\" }]";
+        let saved = SavedSearches::from_toml(bad_routing).unwrap();
+        assert!(matches!(
+            saved.searches[0].to_request(today()),
+            Err(SavedSearchError::Routing { .. })
+        ));
+
+        let far = "[[search]]\nname = \"far\"\n\
+                   slices = [{ from = \"A\", to = \"B\", date = \"+4294967295m\" }]";
+        let saved = SavedSearches::from_toml(far).unwrap();
+        assert!(matches!(
+            saved.searches[0].to_request(today()),
+            Err(SavedSearchError::InvalidDate { value, .. }) if value == "+4294967295m"
+        ));
+
+        let twice = "[[search]]\nname = \"x\"\n[[search]]\nname = \"x\"";
+        assert!(matches!(
+            SavedSearches::from_toml(twice),
+            Err(SavedSearchError::DuplicateName(name)) if name == "x"
+        ));
+        let twice = r#"{"search": [{"name": "x"}, {"name": "x"}]}"#;
+        assert!(matches!(
+            SavedSearches::from_json(twice),
+            Err(SavedSearchError::DuplicateName(_))
+        ));
+
+        let empty = SavedSearch {
+            name: "empty".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            empty.to_request(today()),
+            Err(SavedSearchError::NoSlices { .. })
+        ));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let saved = SavedSearches::from_toml(TOML).unwrap();
+        let text = toml::to_string(&saved).unwrap();
+        let again = SavedSearches::from_toml(&text).unwrap();
+        assert_eq!(
+            again.searches[0].slices[0].date,
+            DateSpec::Next(Weekday::Fri)
+        );
+        assert_eq!(again.searches[0].cabin, Some(Cabin::PremiumCoach));
+    }
+}
@@ -0,0 +1,572 @@
+//! Fare watches: saved searches run on a schedule, with the cheapest price
+//! and the top solutions of every run kept in a local redb database.
+//!
+//! Relative dates in a watch's search are resolved on each run, so
+//! `date = "+30d"` tracks a rolling window while a fixed date tracks one
+//! flight.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::model::money::Money;
+use crate::model::search::{SearchResponse, Solution};
+use crate::saved::{SavedSearch, SavedSearchError};
+
+/// Watch name to JSON [`Watch`].
+const WATCHES: TableDefinition<&str, &[u8]> = TableDefinition::new("watches");
+/// (watch name, milliseconds since the epoch) to JSON [`Observation`].
+const HISTORY: TableDefinition<(&str, i64), &[u8]> = TableDefinition::new("history");
+/// (watch name, sink) to the key of the last alert delivered there.
+const NOTIFIED: TableDefinition<(&str, &str), &str> = TableDefinition::new("notified");
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+pub const DEFAULT_TOP_N: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("watch database: {0}")]
+    Database(#[source] redb::Error),
+
+    #[error("failed to create {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("corrupt watch record: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("a watch named {0:?} already exists")]
+    Exists(String),
+
+    #[error("no watch named {0:?}")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Search(#[from] SavedSearchError),
+}
+
+fn db(error: impl Into<redb::Error>) -> WatchError {
+    WatchError::Database(error.into())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watch {
+    pub name: String,
+    pub search: SavedSearch,
+    /// Seconds between runs.
+    pub interval_secs: u64,
+    /// How many of the cheapest solutions each run keeps.
+    pub top_n: usize,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub alerts: AlertRules,
+}
+
+/// When a run of a watch should send a notification.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRules {
+    /// The cheapest price drops to or below this.
+    pub below: Option<Money>,
+    /// The cheapest price is lower than every earlier run.
+    pub new_low: bool,
+    /// The cheapest price fell at least this many percent since the last run.
+    pub drop_percent: Option<Decimal>,
+}
+
+impl AlertRules {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Watch {
+    /// A watch named after the search, with the default schedule.
+    pub fn new(search: SavedSearch) -> Self {
+        Self {
+            name: search.name.clone(),
+            search,
+            interval_secs: DEFAULT_INTERVAL.as_secs(),
+            top_n: DEFAULT_TOP_N,
+            created_at: Utc::now(),
+            alerts: AlertRules::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn alerts(mut self, alerts: AlertRules) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval_secs = interval.as_secs();
+        self
+    }
+
+    #[must_use]
+    pub fn top_n(mut self, n: usize) -> Self {
+        self.top_n = n;
+        self
+    }
+
+    /// When the watch should next run, given its last run.
+    pub fn next_run(&self, last_run: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        let Some(at) = last_run else {
+            return self.created_at;
+        };
+        i64::try_from(self.interval_secs)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|interval| at.checked_add_signed(interval))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+/// The outcome of one run of a watch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Observation {
+    pub at: DateTime<Utc>,
+    /// Unset when the response has no prices or mixes currencies.
+    pub cheapest: Option<Money>,
+    /// Solutions in the response, before trimming to the top N.
+    pub solution_count: usize,
+    /// The cheapest solutions, cheapest first; in API order if currencies are mixed.
+    pub solutions: Vec<Solution>,
+}
+
+impl Observation {
+    pub fn from_response(response: &SearchResponse, at: DateTime<Utc>, top_n: usize) -> Self {
+        let solutions = response
+            .solution_list
+            .as_ref()
+            .map(|list| list.solutions.as_slice())
+            .unwrap_or_default();
+        let mut ranked: Vec<&Solution> = solutions.iter().collect();
+        // Prices in more than one currency can't be ranked, so there is no
+        // cheapest and the solutions keep API order.
+        let cheapest = Money::try_min(solutions.iter().filter_map(Solution::price))
+            .ok()
+            .flatten();
+        if cheapest.is_some() {
+            // Priced solutions first; the sort is stable so ties keep API order.
+            ranked.sort_by_key(|s| (s.price().is_none(), s.price().map(|p| p.amount())));
+        }
+        Self {
+            at,
+            cheapest,
+            solution_count: solutions.len(),
+            solutions: ranked.into_iter().take(top_n).cloned().collect(),
+        }
+    }
+}
+
+/// How the latest observation compares with the ones before it. Only
+/// earlier prices in the same currency are compared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PriceChange {
+    pub current: Money,
+    pub previous: Option<Money>,
+    pub lowest_before: Option<Money>,
+    /// Percent change from `previous`, to two decimal places.
+    pub percent: Option<Decimal>,
+    /// Lower than every earlier price. False for the first price seen.
+    pub new_low: bool,
+}
+
+impl PriceChange {
+    /// Compare the last observation in `history` (oldest first) with the
+    /// rest. `None` when the last run found no priced solution.
+    pub fn from_history(history: &[Observation]) -> Option<Self> {
+        let (last, earlier) = history.split_last()?;
+        let current = last.cheapest?;
+        let mut earlier = earlier
+            .iter()
+            .filter_map(|o| o.cheapest)
+            .filter(|p| p.currency() == current.currency());
+        let previous = earlier.next_back();
+        let lowest_before = previous.into_iter().chain(earlier).min();
+        Some(Self {
+            current,
+            previous,
+            lowest_before,
+            percent: previous.and_then(|p| percent_change(p, current)),
+            new_low: lowest_before.is_some_and(|low| current < low),
+        })
+    }
+}
+
+/// Percent change from `from` to `to`, or `None` across currencies or
+/// from zero.
+pub fn percent_change(from: Money, to: Money) -> Option<Decimal> {
+    if from.currency() != to.currency() || from.amount().is_zero() {
+        return None;
+    }
+    let change = (to.amount() - from.amount()) / from.amount() * Decimal::ONE_HUNDRED;
+    Some(change.round_dp(2))
+}
+
+pub struct WatchStore {
+    db: Database,
+}
+
+impl WatchStore {
+    /// `<data dir>/ita-matrix/watch.redb`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("ita-matrix").join("watch.redb"))
+    }
+
+    /// Open the database, creating it and its directory if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, WatchError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|source| WatchError::Io {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+        let database = Database::create(path).map_err(db)?;
+        // Create the tables up front so reads never see them missing.
+        let tx = database.begin_write().map_err(db)?;
+        tx.open_table(WATCHES).map_err(db)?;
+        tx.open_table(HISTORY).map_err(db)?;
+        tx.open_table(NOTIFIED).map_err(db)?;
+        tx.commit().map_err(db)?;
+        Ok(Self { db: database })
+    }
+
+    /// Add a watch, failing if one with the same name exists.
+    pub fn add(&self, watch: &Watch) -> Result<(), WatchError> {
+        let value = serde_json::to_vec(watch)?;
+        let tx = self.db.begin_write().map_err(db)?;
+        {
+            let mut table = tx.open_table(WATCHES).map_err(db)?;
+            if table.get(watch.name.as_str()).map_err(db)?.is_some() {
+                return Err(WatchError::Exists(watch.name.clone()));
+            }
+            table
+                .insert(watch.name.as_str(), value.as_slice())
+                .map_err(db)?;
+        }
+        tx.commit().map_err(db)
+    }
+
+    /// Remove a watch, its history and its delivery records.
+    pub fn remove(&self, name: &str) -> Result<(), WatchError> {
+        let tx = self.db.begin_write().map_err(db)?;
+        {
+            let mut watches = tx.open_table(WATCHES).map_err(db)?;
+            if watches.remove(name).map_err(db)?.is_none() {
+                return Err(WatchError::NotFound(name.to_string()));
+            }
+            let mut history = tx.open_table(HISTORY).map_err(db)?;
+            history
+                .extract_from_if((name, i64::MIN)..=(name, i64::MAX), |_, _| true)
+                .map_err(db)?
+                .for_each(drop);
+            let mut notified = tx.open_table(NOTIFIED).map_err(db)?;
+            notified.retain(|(watch, _), _| watch != name).map_err(db)?;
+        }
+        tx.commit().map_err(db)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<Watch>, WatchError> {
+        let tx = self.db.begin_read().map_err(db)?;
+        let table = tx.open_table(WATCHES).map_err(db)?;
+        match table.get(name).map_err(db)? {
+            Some(value) => Ok(Some(serde_json::from_slice(value.value())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every watch, by name.
+    pub fn watches(&self) -> Result<Vec<Watch>, WatchError> {
+        let tx = self.db.begin_read().map_err(db)?;
+        let table = tx.open_table(WATCHES).map_err(db)?;
+        let mut watches = Vec::new();
+        for entry in table.iter().map_err(db)? {
+            let (_, value) = entry.map_err(db)?;
+            watches.push(serde_json::from_slice(value.value())?);
+        }
+        Ok(watches)
+    }
+
+    /// Watches whose next run is at or before `now`.
+    pub fn due(&self, now: DateTime<Utc>) -> Result<Vec<Watch>, WatchError> {
+        let mut due = Vec::new();
+        for watch in self.watches()? {
+            if watch.next_run(self.last_run(&watch.name)?) <= now {
+                due.push(watch);
+            }
+        }
+        Ok(due)
+    }
+
+    pub fn record(&self, name: &str, observation: &Observation) -> Result<(), WatchError> {
+        let value = serde_json::to_vec(observation)?;
+        let tx = self.db.begin_write().map_err(db)?;
+        {
+            if tx
+                .open_table(WATCHES)
+                .map_err(db)?
+                .get(name)
+                .map_err(db)?
+                .is_none()
+            {
+                return Err(WatchError::NotFound(name.to_string()));
+            }
+            let mut table = tx.open_table(HISTORY).map_err(db)?;
+            table
+                .insert((name, observation.at.timestamp_millis()), value.as_slice())
+                .map_err(db)?;
+        }
+        tx.commit().map_err(db)
+    }
+
+    /// Every observation for the watch, oldest first.
+    pub fn history(&self, name: &str) -> Result<Vec<Observation>, WatchError> {
+        let tx = self.db.begin_read().map_err(db)?;
+        let table = tx.open_table(HISTORY).map_err(db)?;
+        let mut history = Vec::new();
+        for entry in table
+            .range((name, i64::MIN)..=(name, i64::MAX))
+            .map_err(db)?
+        {
+            let (_, value) = entry.map_err(db)?;
+            history.push(serde_json::from_slice(value.value())?);
+        }
+        Ok(history)
+    }
+
+    /// The key of the last alert for the watch delivered to `sink`.
+    pub fn last_notified(&self, name: &str, sink: &str) -> Result<Option<String>, WatchError> {
+        let tx = self.db.begin_read().map_err(db)?;
+        let table = tx.open_table(NOTIFIED).map_err(db)?;
+        Ok(table
+            .get((name, sink))
+            .map_err(db)?
+            .map(|key| key.value().to_string()))
+    }
+
+    pub fn mark_notified(&self, name: &str, sink: &str, key: &str) -> Result<(), WatchError> {
+        let tx = self.db.begin_write().map_err(db)?;
+        tx.open_table(NOTIFIED)
+            .map_err(db)?
+            .insert((name, sink), key)
+            .map_err(db)?;
+        tx.commit().map_err(db)
+    }
+
+    pub fn last_run(&self, name: &str) -> Result<Option<DateTime<Utc>>, WatchError> {
+        let tx = self.db.begin_read().map_err(db)?;
+        let table = tx.open_table(HISTORY).map_err(db)?;
+        let last = table
+            .range((name, i64::MIN)..=(name, i64::MAX))
+            .map_err(db)?
+            .next_back()
+            .transpose()
+            .map_err(db)?;
+        Ok(last.and_then(|(key, _)| DateTime::from_timestamp_millis(key.value().1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saved::SavedSearches;
+
+    fn search(name: &str) -> SavedSearch {
+        let toml = format!(
+            "[[search]]\nname = \"{name}\"\n\
+             slices = [{{ from = \"BOS\", to = \"LAX\", date = \"+30d\" }}]"
+        );
+        SavedSearches::from_toml(&toml).unwrap().searches.remove(0)
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2026-10-01T{hour:02}:00:00Z"))
+            .unwrap()
+            .to_utc()
+    }
+
+    fn observed(hour: u32, price: Option<&str>) -> Observation {
+        Observation {
+            at: at(hour),
+            cheapest: price.map(|p| p.parse().unwrap()),
+            solution_count: 0,
+            solutions: Vec::new(),
+        }
+    }
+
+    fn store() -> (tempfile::TempDir, WatchStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = WatchStore::open(dir.path().join("nested").join("watch.redb")).unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn keeps_the_cheapest_solutions() {
+        let response: SearchResponse = serde_json::from_value(serde_json::json!({
+            "solutionList": { "solutions": [
+                { "id": "a", "displayTotal": "USD300.00" },
+                { "id": "b" },
+                { "id": "c", "displayTotal": "USD120.00" },
+                { "id": "d", "displayTotal": "USD250.00" }
+            ]}
+        }))
+        .unwrap();
+        let observation = Observation::from_response(&response, at(0), 2);
+        assert_eq!(observation.cheapest, Some("USD120.00".parse().unwrap()));
+        assert_eq!(observation.solution_count, 4);
+        let ids: Vec<&str> = observation
+            .solutions
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        assert_eq!(ids, ["c", "d"]);
+    }
+
+    #[test]
+    fn mixed_currencies_have_no_cheapest() {
+        let response: SearchResponse = serde_json::from_value(serde_json::json!({
+            "solutionList": { "solutions": [
+                { "id": "a", "displayTotal": "USD100.00" },
+                { "id": "b", "displayTotal": "EUR900.00" },
+                { "id": "c", "displayTotal": "USD80.00" }
+            ]}
+        }))
+        .unwrap();
+        let observation = Observation::from_response(&response, at(0), 2);
+        assert_eq!(observation.cheapest, None);
+        let ids: Vec<&str> = observation
+            .solutions
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[test]
+    fn price_change_and_new_low() {
+        let history = [
+            observed(0, Some("USD200.00")),
+            observed(1, Some("USD150.00")),
+            observed(2, None),
+            observed(3, Some("EUR90.00")),
+            observed(4, Some("USD160.00")),
+        ];
+        let change = PriceChange::from_history(&history).unwrap();
+        assert_eq!(change.previous, Some("USD150.00".parse().unwrap()));
+        assert_eq!(change.lowest_before, Some("USD150.00".parse().unwrap()));
+        assert_eq!(change.percent, Some(Decimal::new(667, 2)));
+        assert!(!change.new_low);
+
+        let change = PriceChange::from_history(&history[..2]).unwrap();
+        assert_eq!(change.percent, Some(Decimal::new(-25, 0)));
+        assert!(change.new_low);
+
+        let first = PriceChange::from_history(&history[..1]).unwrap();
+        assert_eq!((first.previous, first.new_low), (None, false));
+        assert!(PriceChange::from_history(&history[..3]).is_none());
+    }
+
+    #[test]
+    fn add_list_and_remove() {
+        let (_dir, store) = store();
+        store.add(&Watch::new(search("b"))).unwrap();
+        store.add(&Watch::new(search("a")).top_n(3)).unwrap();
+        assert!(matches!(
+            store.add(&Watch::new(search("a"))),
+            Err(WatchError::Exists(_))
+        ));
+
+        let names: Vec<String> = store
+            .watches()
+            .unwrap()
+            .into_iter()
+            .map(|w| w.name)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(store.get("a").unwrap().unwrap().top_n, 3);
+
+        store.record("a", &observed(1, Some("USD1.00"))).unwrap();
+        store.mark_notified("a", "webhook", "k1").unwrap();
+        store.mark_notified("b", "webhook", "k2").unwrap();
+        assert_eq!(
+            store.last_notified("a", "webhook").unwrap().as_deref(),
+            Some("k1")
+        );
+        store.remove("a").unwrap();
+        assert!(store.get("a").unwrap().is_none());
+        assert!(store.history("a").unwrap().is_empty());
+        assert_eq!(store.last_notified("a", "webhook").unwrap(), None);
+        assert!(store.last_notified("b", "webhook").unwrap().is_some());
+        assert!(matches!(store.remove("a"), Err(WatchError::NotFound(_))));
+    }
+
+    #[test]
+    fn history_survives_reopen_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watch.redb");
+        {
+            let store = WatchStore::open(&path).unwrap();
+            store.add(&Watch::new(search("bos-lax"))).unwrap();
+            store.add(&Watch::new(search("bos-lax-2"))).unwrap();
+            store
+                .record("bos-lax", &observed(5, Some("USD90.00")))
+                .unwrap();
+            store
+                .record("bos-lax", &observed(2, Some("USD80.00")))
+                .unwrap();
+            store
+                .record("bos-lax-2", &observed(9, Some("USD70.00")))
+                .unwrap();
+            assert!(matches!(
+                store.record("other", &observed(1, None)),
+                Err(WatchError::NotFound(_))
+            ));
+        }
+        let store = WatchStore::open(&path).unwrap();
+        let hours: Vec<DateTime<Utc>> = store
+            .history("bos-lax")
+            .unwrap()
+            .iter()
+            .map(|o| o.at)
+            .collect();
+        assert_eq!(hours, [at(2), at(5)]);
+        assert_eq!(store.last_run("bos-lax").unwrap(), Some(at(5)));
+    }
+
+    #[test]
+    fn due_follows_interval() {
+        let (_dir, store) = store();
+        let mut watch = Watch::new(search("w")).interval(Duration::from_secs(3 * 60 * 60));
+        watch.created_at = at(0);
+        store.add(&watch).unwrap();
+        assert_eq!(store.due(at(0)).unwrap().len(), 1);
+
+        store.record("w", &observed(1, None)).unwrap();
+        assert!(store.due(at(3)).unwrap().is_empty());
+        assert_eq!(store.due(at(4)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn huge_intervals_never_come_due() {
+        for secs in [100_000_000 * 86_400, u64::MAX] {
+            let watch = Watch::new(search("w")).interval(Duration::from_secs(secs));
+            assert_eq!(watch.next_run(Some(at(0))), DateTime::<Utc>::MAX_UTC);
+        }
+    }
+}